@@ -0,0 +1,100 @@
+/*
+With the embedded-tables feature, this generates the move and pruning tables of the small coordinates while
+building, so they are compiled into the binary instead of being generated on the first run. The tables are
+built by the solver's own table code, which is included here, and written in the format of the disk cache.
+*/
+
+// The solver's modules are only partly used here, and are included at the root so their crate paths resolve
+#![cfg_attr(feature = "embedded-tables", allow(dead_code))]
+
+#[cfg(feature = "embedded-tables")]
+#[path = "src/error.rs"]
+mod error;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/turndef.rs"]
+mod turndef;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/moveset.rs"]
+mod moveset;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/rawcube.rs"]
+mod rawcube;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/coords/mod.rs"]
+mod coords;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/utils/mod.rs"]
+mod utils;
+#[cfg(feature = "embedded-tables")]
+#[path = "src/tables"]
+mod tables {
+    pub mod cache;
+    pub mod movetables;
+    pub mod progress;
+    pub mod pruningtables;
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "embedded-tables")]
+    embedded::generate();
+}
+
+#[cfg(feature = "embedded-tables")]
+mod embedded {
+    use std::env;
+    use std::fmt::Write;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::coords::BasicCoordinate;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::cp::CornerPermCoord;
+    use crate::coords::basic_coords::e_m_s_edges::{ESliceEdgePermCoord, MSliceEdgePermCoord, SSliceEdgePermCoord};
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::tables::cache::{self, TableHeader, TableKind};
+    use crate::tables::movetables::MoveTables;
+    use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+
+    pub fn generate() {
+        for dir in ["src/coords", "src/tables", "src/utils"] {
+            println!("cargo:rerun-if-changed={}", dir);
+        }
+        for file in ["src/error.rs", "src/turndef.rs", "src/moveset.rs", "src/rawcube.rs"] {
+            println!("cargo:rerun-if-changed={}", file);
+        }
+
+        let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+        let mut files = Vec::new();
+        files.extend(write_tables(EOFBCoord::new(), &out_dir));
+        files.extend(write_tables(COUDCoord::new(), &out_dir));
+        files.extend(write_tables(CornerPermCoord::new(), &out_dir));
+        files.extend(write_tables(ESliceEdgePermCoord::new(), &out_dir));
+        files.extend(write_tables(MSliceEdgePermCoord::new(), &out_dir));
+        files.extend(write_tables(SSliceEdgePermCoord::new(), &out_dir));
+
+        let mut source = String::from("static EMBEDDED_TABLES: &[(&str, &[u8])] = &[\n");
+        for file in files {
+            writeln!(source, "    ({:?}, include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\"))),", file, file).unwrap();
+        }
+        source.push_str("];\n");
+        fs::write(out_dir.join("embedded_tables.rs"), source).unwrap();
+    }
+
+    // Writes the move and pruning tables of a coordinate over its own turns, returning the names of the files
+    fn write_tables<C: BasicCoordinate>(coord_type: C, out_dir: &Path) -> Vec<String> {
+        let identity = format!("{:?}", coord_type);
+        let move_tables = MoveTables::new_basic_table(coord_type, &coord_type.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(coord_type, &move_tables).unwrap();
+
+        let move_header = TableHeader::new(TableKind::Move, &identity, move_tables.get_turns(), coord_type.get_size());
+        let pruning_header = TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), &identity, move_tables.get_turns(), coord_type.get_size());
+        let mut files = Vec::new();
+        for (header, payload) in [(move_header, move_tables.to_bytes()), (pruning_header, pruning_table.to_bytes())] {
+            let file = header.get_file_name();
+            cache::write_table_file(&out_dir.join(&file), &header, &payload).unwrap();
+            files.push(file);
+        }
+        files
+    }
+}
@@ -1,7 +1,6 @@
-use crate::coords::{Coordinate, BasicCoordinate};
+use crate::coords::PieceCoordinate;
 use crate::utils::coordutils::{coord_to_twist, twist_to_coord};
-use crate::rawcube::{RawState, StateList, Twist, TurnEffect};
-use crate::turndef::Turn;
+use crate::rawcube::{RawState, StateList, Twist};
 
 
 const NUM_CORNER_TWIST_COMBINATIONS: usize = 2187;
@@ -18,48 +17,19 @@ impl COUDCoord {
     }
 }
 
-impl Coordinate for COUDCoord {
-    fn get_size(&self) -> usize {
-        NUM_CORNER_TWIST_COMBINATIONS
-    }
-
-    fn get_solved_coords(&self) -> Vec<usize> {
-        vec![0]
-    }
+impl PieceCoordinate for COUDCoord {
+    const SIZE: usize = NUM_CORNER_TWIST_COMBINATIONS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
 
-    fn get_allowed_turns(&self) -> Vec<Turn> {
-        // All outer layer turns are allowed
-        Turn::get_outer_layer_turns()
-    }
-
-    fn apply_turn(&self, coord: usize, turn: &Turn) -> usize {
-        let mut twists = get_twists(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_twists_statelist(&mut twists);
-
-        twist_to_coord(&twists.as_slice()[0..7])
-    }
-}
-
-impl BasicCoordinate for COUDCoord {
-
-    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+    fn pieces_to_coord(state: &RawState) -> usize {
         twist_to_coord(&state.twists.as_slice()[0..7])
     }
 
-    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+    fn coord_to_pieces(coord: usize) -> RawState {
         let mut state = RawState::solved();
         state.twists = get_twists(coord);
         state
     }
-
-    fn apply_raw_turn(coord: usize, turn: &Turn) -> usize {
-        let mut twists = get_twists(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_twists_statelist(&mut twists);
-
-        twist_to_coord(&twists.as_slice()[0..7])
-    }
 }
 
 fn get_twists(coord: usize) -> StateList<Twist> {
@@ -1,7 +1,6 @@
-use crate::coords::{Coordinate, BasicCoordinate};
+use crate::coords::PieceCoordinate;
 use crate::utils::coordutils::{coord_to_permutation, permutation_to_coord};
-use crate::rawcube::{RawState, StateList, TurnEffect, Corner};
-use crate::turndef::Turn;
+use crate::rawcube::{RawState, StateList, Corner};
 
 /// Coordinate to represent corner permutation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -16,48 +15,19 @@ impl CornerPermCoord {
     }
 }
 
-impl Coordinate for CornerPermCoord {
-    fn get_size(&self) -> usize {
-        NUM_CORNER_PERM_COMBINATIONS
-    }
-
-    fn get_solved_coords(&self) -> Vec<usize> {
-        vec![0]
-    }
+impl PieceCoordinate for CornerPermCoord {
+    const SIZE: usize = NUM_CORNER_PERM_COMBINATIONS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
 
-    fn get_allowed_turns(&self) -> Vec<Turn> {
-        // All outer layer turns are allowed
-        Turn::get_outer_layer_turns()
-    }
-
-    fn apply_turn(&self, coord: usize, turn: &Turn) -> usize {
-        let mut corners = get_corners(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_corners_statelist(&mut corners);
-
-        permutation_to_coord(corners.as_slice())
-    }
-}
-
-impl BasicCoordinate for CornerPermCoord {
-
-    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+    fn pieces_to_coord(state: &RawState) -> usize {
         permutation_to_coord(state.corners.as_slice())
     }
 
-    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+    fn coord_to_pieces(coord: usize) -> RawState {
         let mut state = RawState::solved();
         state.corners = get_corners(coord);
         state
     }
-
-    fn apply_raw_turn(coord: usize, turn: &Turn) -> usize {
-        let mut corners = get_corners(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_corners_statelist(&mut corners);
-
-        permutation_to_coord(corners.as_slice())
-    }
 }
 
 fn get_corners(coord: usize) -> StateList<Corner> {
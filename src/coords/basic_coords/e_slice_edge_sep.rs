@@ -1,7 +1,6 @@
-use crate::coords::{Coordinate, BasicCoordinate};
+use crate::coords::PieceCoordinate;
 use crate::utils::coordutils::{piece_distibution_to_coord, coord_to_piece_distribution};
-use crate::rawcube::{RawState, StateList, TurnEffect, Edge, Swap};
-use crate::turndef::Turn;
+use crate::rawcube::{RawState, StateList, Edge, Swap};
 
 
 const NUM_EDGE_SEP_COMBINATIONS: usize = 495;
@@ -25,50 +24,19 @@ impl ESliceEdgeSepCoord {
     }
 }
 
-impl Coordinate for ESliceEdgeSepCoord {
+impl PieceCoordinate for ESliceEdgeSepCoord {
+    const SIZE: usize = NUM_EDGE_SEP_COMBINATIONS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
 
-    fn get_size(&self) -> usize {
-        NUM_EDGE_SEP_COMBINATIONS
-    }
-
-    fn get_solved_coords(&self) -> Vec<usize> {
-        vec![0]
-    }
-
-    fn get_allowed_turns(&self) -> Vec<Turn> {
-        // All outer layer turns are allowed
-        Turn::get_outer_layer_turns()
-    }
-
-    fn apply_turn(&self, coord: usize, turn: &Turn) -> usize {
-        let mut edges = get_edges(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_edges_statelist(&mut edges);
-
-        edge_sep_to_coord(&edges)
-    }
-}
-
-impl BasicCoordinate for ESliceEdgeSepCoord {
-
-    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+    fn pieces_to_coord(state: &RawState) -> usize {
         edge_sep_to_coord(&state.edges)
     }
 
-    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+    fn coord_to_pieces(coord: usize) -> RawState {
         let mut state = RawState::solved();
         state.edges = get_edges(coord);
         state
     }
-
-    fn apply_raw_turn( coord: usize, turn: &Turn) -> usize {
-        let mut edges = get_edges(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_edges_statelist(&mut edges);
-
-        edge_sep_to_coord(&edges)
-    }
-
 }
 
 fn edge_sep_to_coord(edge_state: &StateList<Edge>) -> usize {
@@ -1,7 +1,6 @@
-use crate::coords::{Coordinate, BasicCoordinate};
+use crate::coords::PieceCoordinate;
 use crate::utils::coordutils::{coord_to_flip, flip_to_coord};
-use crate::rawcube::{RawState, StateList, Flip, TurnEffect};
-use crate::turndef::Turn;
+use crate::rawcube::{RawState, StateList, Flip};
 
 /// Coordinate for edge orientation relative to the FB axis
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -15,32 +14,11 @@ impl EOFBCoord {
     }
 }
 
-impl Coordinate for EOFBCoord {
+impl PieceCoordinate for EOFBCoord {
+    const SIZE: usize = 2048;
+    const SOLVED_COORDS: &'static [usize] = &[0];
 
-    fn get_size(&self) -> usize {
-        2048
-    }
-
-    fn get_solved_coords(&self) -> Vec<usize> {
-        vec![0]
-    }
-
-    fn get_allowed_turns(&self) -> Vec<Turn> {
-        // All outer layer turns are allowed
-        Turn::get_outer_layer_turns()
-    }
-
-    fn apply_turn(&self, coord: usize, turn: &Turn) -> usize {
-        let mut flips = get_flips(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_flips_statelist(&mut flips);
-        flip_to_coord(&flips.as_slice()[0..11])
-    }
-}
-
-impl BasicCoordinate for EOFBCoord {
-
-    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+    fn pieces_to_coord(state: &RawState) -> usize {
         let flips = state.flips.as_slice();
         // We only care about the first 11 flips
         // The last flip is determined by the first 11
@@ -48,18 +26,11 @@ impl BasicCoordinate for EOFBCoord {
         flip_to_coord(&flips[0..11])
     }
 
-    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+    fn coord_to_pieces(coord: usize) -> RawState {
         let mut state = RawState::solved();
         state.flips = get_flips(coord);
         state
     }
-
-    fn apply_raw_turn(coord: usize, turn: &Turn) -> usize {
-        let mut flips = get_flips(coord);
-        let turn_effect = TurnEffect::from_turn(turn);
-        turn_effect.apply_to_flips_statelist(&mut flips);
-        flip_to_coord(&flips.as_slice()[0..11])
-    }
 }
 
 fn get_flips(coord: usize) -> StateList<Flip> {
@@ -4,3 +4,4 @@ pub mod e_slice_edge_sep;
 pub mod cp;
 pub mod u_d_corner_perms;
 pub mod e_m_s_edges;
+pub mod piece_subset;
@@ -0,0 +1,298 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::coords::{Coordinate, BasicCoordinate, apply_turn_to_pieces};
+use crate::utils::coordutils::{
+    coord_to_flip, flip_to_coord, coord_to_twist, twist_to_coord,
+    get_piece_subset_size, piece_subset_to_coord, coord_to_piece_subset
+};
+use crate::rawcube::{RawState, StateList, Corner, Edge, Twist, Flip};
+use crate::moveset::MoveSet;
+use crate::turndef::Turn;
+
+
+const NUM_CORNERS: usize = 8;
+const NUM_EDGES: usize = 12;
+
+
+/*
+PieceSubsetCoord tracks any set of corners and edges chosen at runtime, such as the pieces of a block, so new
+targets need no new coordinate module. Like the slice coordinates, it stores where the tracked corners are and
+how they are permuted amongst themselves, and the same for the tracked edges. The twists of the tracked corners
+and the flips of the tracked edges may be stored as well. When every corner is tracked with its twist, the twist
+of the last corner is fixed by the others and is left out, and the same goes for the flips of the edges.
+
+The size is only known at runtime, so this implements Coordinate directly rather than PieceCoordinate. Tables
+grow quickly with the number of tracked pieces, so subsets should stay around the size of a block.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PieceSubsetCoord {
+    // One bit for each tracked piece, indexed by its solved position
+    corners: u8,
+    edges: u16,
+    corner_orientation: bool,
+    edge_orientation: bool,
+}
+
+impl PieceSubsetCoord {
+    /// Tracks where the given corners and edges are, without their orientation
+    pub fn new(corners: &[Corner], edges: &[Edge]) -> Self {
+        Self {
+            corners: corners.iter().fold(0, |mask, corner| mask | 1 << *corner as u8),
+            edges: edges.iter().fold(0, |mask, edge| mask | 1 << *edge as u16),
+            corner_orientation: false,
+            edge_orientation: false,
+        }
+    }
+
+    /// Also tracks the given corner
+    pub fn with_corner(self, corner: Corner) -> Self {
+        Self { corners: self.corners | 1 << corner as u8, ..self }
+    }
+
+    /// Also tracks the given edge
+    pub fn with_edge(self, edge: Edge) -> Self {
+        Self { edges: self.edges | 1 << edge as u16, ..self }
+    }
+
+    /// Also tracks the twists of the tracked corners
+    pub fn with_corner_orientation(self) -> Self {
+        Self { corner_orientation: true, ..self }
+    }
+
+    /// Also tracks the flips of the tracked edges
+    pub fn with_edge_orientation(self) -> Self {
+        Self { edge_orientation: true, ..self }
+    }
+
+    /// The tracked corners, in ascending order
+    pub fn get_corners(&self) -> Vec<Corner> {
+        (0..NUM_CORNERS).filter(|index| self.corners >> index & 1 == 1).map(|index| index.into()).collect()
+    }
+
+    /// The tracked edges, in ascending order
+    pub fn get_edges(&self) -> Vec<Edge> {
+        (0..NUM_EDGES).filter(|index| self.edges >> index & 1 == 1).map(|index| index.into()).collect()
+    }
+
+    // The number of tracked corners whose twists are stored
+    fn get_num_twists(&self) -> usize {
+        let num_corners = self.corners.count_ones() as usize;
+        match self.corner_orientation {
+            true if num_corners == NUM_CORNERS => num_corners - 1,
+            true => num_corners,
+            false => 0,
+        }
+    }
+
+    // The number of tracked edges whose flips are stored
+    fn get_num_flips(&self) -> usize {
+        let num_edges = self.edges.count_ones() as usize;
+        match self.edge_orientation {
+            true if num_edges == NUM_EDGES => num_edges - 1,
+            true => num_edges,
+            false => 0,
+        }
+    }
+
+    fn get_num_twist_coords(&self) -> usize {
+        3usize.pow(self.get_num_twists() as u32)
+    }
+
+    fn get_num_flip_coords(&self) -> usize {
+        1 << self.get_num_flips()
+    }
+
+    fn get_corner_size(&self) -> usize {
+        get_piece_subset_size(NUM_CORNERS, self.corners.count_ones() as usize) * self.get_num_twist_coords()
+    }
+
+    fn get_edge_size(&self) -> usize {
+        get_piece_subset_size(NUM_EDGES, self.edges.count_ones() as usize) * self.get_num_flip_coords()
+    }
+
+    fn get_corner_coord(&self, state: &RawState) -> usize {
+        let corners = self.get_corners();
+        let positions = get_positions(state.corners.as_slice(), &corners);
+        let twists: Vec<Twist> = positions.iter().take(self.get_num_twists())
+            .map(|position| *state.twists.get(position))
+            .collect();
+        piece_subset_to_coord(state.corners.as_slice(), &corners) * self.get_num_twist_coords() + twist_to_coord(&twists)
+    }
+
+    fn get_edge_coord(&self, state: &RawState) -> usize {
+        let edges = self.get_edges();
+        let positions = get_positions(state.edges.as_slice(), &edges);
+        let flips: Vec<Flip> = positions.iter().take(self.get_num_flips())
+            .map(|position| *state.flips.get(position))
+            .collect();
+        piece_subset_to_coord(state.edges.as_slice(), &edges) * self.get_num_flip_coords() + flip_to_coord(&flips)
+    }
+
+    fn set_corners(&self, coord: usize, state: &mut RawState) {
+        let corners = self.get_corners();
+        let num_twist_coords = self.get_num_twist_coords();
+        state.corners = StateList::new(coord_to_piece_subset(coord / num_twist_coords, &corners, state.corners.as_slice()));
+
+        let mut twists = coord_to_twist(coord % num_twist_coords, self.get_num_twists());
+        if twists.len() < corners.len() && self.corner_orientation {
+            let total_twist: usize = twists.iter().map(|twist| *twist as usize).sum();
+            twists.push((3 - (total_twist % 3)).into());
+        }
+        for (position, twist) in get_positions(state.corners.as_slice(), &corners).iter().zip(twists) {
+            state.twists.set(position, &twist);
+        }
+    }
+
+    fn set_edges(&self, coord: usize, state: &mut RawState) {
+        let edges = self.get_edges();
+        let num_flip_coords = self.get_num_flip_coords();
+        state.edges = StateList::new(coord_to_piece_subset(coord / num_flip_coords, &edges, state.edges.as_slice()));
+
+        let mut flips = coord_to_flip(coord % num_flip_coords, self.get_num_flips());
+        if flips.len() < edges.len() && self.edge_orientation {
+            let flip_last = flips.iter().filter(|flip| **flip == Flip::Bad).count() % 2 == 1;
+            flips.push(if flip_last { Flip::Bad } else { Flip::Good });
+        }
+        for (position, flip) in get_positions(state.edges.as_slice(), &edges).iter().zip(flips) {
+            state.flips.set(position, &flip);
+        }
+    }
+}
+
+// Names the tracked pieces, such as PieceSubsetCoord-UFR-CO-UF-UR-FR. The tables of the coordinate are
+// named by this in the disk cache, so it is different for every subset.
+impl fmt::Debug for PieceSubsetCoord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PieceSubsetCoord")?;
+        for corner in self.get_corners() {
+            write!(f, "-{:?}", corner)?;
+        }
+        if self.corner_orientation {
+            write!(f, "-CO")?;
+        }
+        for edge in self.get_edges() {
+            write!(f, "-{:?}", edge)?;
+        }
+        if self.edge_orientation {
+            write!(f, "-EO")?;
+        }
+        Ok(())
+    }
+}
+
+impl Coordinate for PieceSubsetCoord {
+
+    // Saturates rather than overflowing for the largest subsets, whose tables could never be built anyway
+    fn get_size(&self) -> usize {
+        self.get_corner_size().saturating_mul(self.get_edge_size())
+    }
+
+    fn get_solved_coords(&self) -> Vec<usize> {
+        vec![self.convert_raw_state_to_coord(&RawState::solved())]
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        // All outer layer turns are allowed
+        MoveSet::full()
+    }
+
+    fn apply_turn(&self, coord: usize, turn: &Turn) -> Result<usize> {
+        let state = apply_turn_to_pieces(self.convert_coord_to_example_raw_state(coord), turn, false)?;
+        Ok(self.convert_raw_state_to_coord(&state))
+    }
+}
+
+impl BasicCoordinate for PieceSubsetCoord {
+
+    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+        self.get_corner_coord(state) * self.get_edge_size() + self.get_edge_coord(state)
+    }
+
+    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+        let edge_size = self.get_edge_size();
+        let mut state = RawState::solved();
+        self.set_corners(coord / edge_size, &mut state);
+        self.set_edges(coord % edge_size, &mut state);
+        state
+    }
+}
+
+// Finds the position of each of the tracked pieces
+fn get_positions<T: PartialEq>(pieces: &[T], tracked: &[T]) -> Vec<usize> {
+    tracked.iter()
+        .map(|piece| pieces.iter().position(|other| other == piece).expect("Every piece is somewhere"))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Algorithm;
+    use crate::coords::basic_coords::e_m_s_edges::{ESliceEdgePermCoord, E_SLICE_EDGES};
+    use crate::coords::basic_coords::u_d_corner_perms::DCornerPermCoord;
+    use crate::tables::movetables::MoveTables;
+    use crate::tables::pruningtables::PruningTable;
+
+    #[test]
+    fn test_matches_slice_coords() {
+        let e_slice = PieceSubsetCoord::new(&[], &E_SLICE_EDGES);
+        let d_corners = PieceSubsetCoord::new(&[Corner::DBR, Corner::DFR, Corner::DFL, Corner::DBL], &[]);
+        assert_eq!(e_slice.get_size(), ESliceEdgePermCoord::new().get_size());
+        assert_eq!(d_corners.get_size(), DCornerPermCoord::new().get_size());
+
+        let mut state = RawState::solved();
+        for turn in Turn::get_vec_from_alg_string("R U' F2 L D B' R2 U") {
+            assert_eq!(e_slice.convert_raw_state_to_coord(&state), ESliceEdgePermCoord::new().convert_raw_state_to_coord(&state));
+            assert_eq!(d_corners.convert_raw_state_to_coord(&state), DCornerPermCoord::new().convert_raw_state_to_coord(&state));
+            state.apply_turn(&turn);
+        }
+    }
+
+    #[test]
+    fn test_orientation() {
+        let block = PieceSubsetCoord::new(&[Corner::UFR], &[Edge::UF, Edge::UR, Edge::FR]).with_corner_orientation();
+        assert_eq!(format!("{:?}", block), "PieceSubsetCoord-UFR-CO-UF-UR-FR");
+        assert_eq!(block.get_size(), 8 * 3 * 12 * 11 * 10);
+
+        // Every corner tracked with its twist leaves out the twist of the last one
+        let all_corners: Vec<Corner> = (0..8).map(|index| index.into()).collect();
+        let corners = PieceSubsetCoord::new(&all_corners, &[]).with_corner_orientation();
+        assert_eq!(corners.get_size(), 40_320 * 2_187);
+
+        let coord_type = PieceSubsetCoord::new(&[Corner::UFR, Corner::DBL], &[Edge::UF, Edge::FR]).with_corner_orientation().with_edge_orientation();
+        for coord in [0, 1, 1_000, coord_type.get_size() - 1] {
+            let state = coord_type.convert_coord_to_example_raw_state(coord);
+            assert_eq!(coord_type.convert_raw_state_to_coord(&state), coord);
+        }
+
+        // Twists and flips are followed through turns
+        let mut state = RawState::solved();
+        let mut coord = coord_type.get_solved_coords()[0];
+        for turn in Turn::get_vec_from_alg_string("F R U' B L2 D F' R").to_base_turns() {
+            state.apply_turn(&turn);
+            coord = coord_type.apply_turn(coord, &turn).unwrap();
+            assert_eq!(coord, coord_type.convert_raw_state_to_coord(&state));
+        }
+    }
+
+    #[test]
+    fn test_block_tables() {
+        // A corner and edge pair with one more edge, along with their orientation
+        let block = PieceSubsetCoord::new(&[Corner::DFR], &[Edge::FR, Edge::DR])
+            .with_corner_orientation()
+            .with_edge_orientation();
+        let move_tables = MoveTables::new_basic_table(block, &block.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(block, &move_tables).unwrap();
+        move_tables.verify().unwrap();
+        pruning_table.verify(&move_tables).unwrap();
+
+        let mut state = RawState::solved();
+        assert_eq!(pruning_table.get_distance(block.convert_raw_state_to_coord(&state)).unwrap(), 0);
+        state.apply_algorithm(&Turn::get_vec_from_alg_string("U L"));
+        assert_eq!(pruning_table.get_distance(block.convert_raw_state_to_coord(&state)).unwrap(), 0);
+        state.apply_algorithm(&Turn::get_vec_from_alg_string("R"));
+        assert_eq!(pruning_table.get_distance(block.convert_raw_state_to_coord(&state)).unwrap(), 1);
+    }
+}
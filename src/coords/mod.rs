@@ -1,10 +1,13 @@
 pub mod basic_coords;
+pub mod phase_coords;
 
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::marker::Sync;
 
-use crate::rawcube::RawState;
+use crate::error::{Error, Result};
+use crate::moveset::MoveSet;
+use crate::rawcube::{RawState, TurnEffect};
 use crate::turndef::Turn;
 
 /*
@@ -13,17 +16,207 @@ A coordinate system must implement methods to convert to and from a RawState
 pub trait Coordinate : Copy + Clone + PartialEq + Eq + Hash + Debug + Sync {
     fn get_size(&self) -> usize;
     fn get_solved_coords(&self) -> Vec<usize>;
-    fn get_allowed_turns(&self) -> Vec<Turn>;
+    fn get_allowed_turns(&self) -> MoveSet;
 
-    fn apply_turn(&self, coord: usize, turn: &Turn) -> usize;
+    fn apply_turn(&self, coord: usize, turn: &Turn) -> Result<usize>;
 }
 
 pub trait BasicCoordinate : Coordinate {
     fn convert_raw_state_to_coord(&self, state: &RawState) -> usize;
     fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState;
+}
+
+/*
+Most coordinates describe some of the pieces of a cube, and only need to say how those pieces convert to and
+from a coordinate. Every PieceCoordinate is a Coordinate and a BasicCoordinate, with turns applied the same way
+for all of them: the pieces of the coordinate are put in an example state, the turn is applied to the state,
+and the state is converted back to a coordinate.
+*/
+pub trait PieceCoordinate : Copy + Clone + PartialEq + Eq + Hash + Debug + Sync {
+    /// The number of coordinates
+    const SIZE: usize;
+    /// The coordinates of the states that count as solved
+    const SOLVED_COORDS: &'static [usize];
+    /// Whether compound turns are applied as a whole, one base turn after another. Coordinates only defined
+    /// within a subgroup need this, as the base turns of a compound turn may leave the subgroup. Otherwise
+    /// compound turns are rejected, and move tables build them from the tables of their base turns.
+    const WHOLE_COMPOUND_TURNS: bool = false;
 
-    // Basic coordinates need to implement a static method to apply a turn to a coordinate
-    // This makes it easier to parallelize move table generation
-    fn apply_raw_turn(coord: usize, turn: &Turn) -> usize;
+    fn pieces_to_coord(state: &RawState) -> usize;
+    fn coord_to_pieces(coord: usize) -> RawState;
+
+    fn get_allowed_turns() -> MoveSet {
+        // All outer layer turns are allowed
+        MoveSet::full()
+    }
+
+    /// Whether the coordinate describes the pieces of a state. Coordinates only defined within a subgroup
+    /// are meaningless outside of it, so turns that leave the subgroup give an error instead of a coordinate.
+    fn is_in_domain(_state: &RawState) -> bool {
+        true
+    }
 }
 
+impl<C: PieceCoordinate> Coordinate for C {
+    fn get_size(&self) -> usize {
+        C::SIZE
+    }
+
+    fn get_solved_coords(&self) -> Vec<usize> {
+        C::SOLVED_COORDS.to_vec()
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        C::get_allowed_turns()
+    }
+
+    fn apply_turn(&self, coord: usize, turn: &Turn) -> Result<usize> {
+        let state = apply_turn_to_pieces(C::coord_to_pieces(coord), turn, C::WHOLE_COMPOUND_TURNS)?;
+        if !C::is_in_domain(&state) {
+            return Err(Error::LeavesSubgroup(*turn));
+        }
+        Ok(C::pieces_to_coord(&state))
+    }
+}
+
+impl<C: PieceCoordinate> BasicCoordinate for C {
+    fn convert_raw_state_to_coord(&self, state: &RawState) -> usize {
+        C::pieces_to_coord(state)
+    }
+
+    fn convert_coord_to_example_raw_state(&self, coord: usize) -> RawState {
+        C::coord_to_pieces(coord)
+    }
+}
+
+/// Applies a turn to the example state of a coordinate, which is how every coordinate applies turns.
+/// Compound turns are rejected unless whole_compound_turns is set, as for PieceCoordinate::WHOLE_COMPOUND_TURNS.
+pub(crate) fn apply_turn_to_pieces(mut state: RawState, turn: &Turn, whole_compound_turns: bool) -> Result<RawState> {
+    match whole_compound_turns {
+        true => for base_turn in turn.to_base_turns() {
+            TurnEffect::from_turn(&base_turn)?.apply(&mut state);
+        },
+        false => TurnEffect::from_turn(turn)?.apply(&mut state),
+    }
+    Ok(state)
+}
+
+/// Declares a coordinate for where a subset of the corners or edges are and how they are permuted amongst
+/// themselves. The pieces of the subset must be given in ascending order, and the solved coordinate is given
+/// so that it can be a constant.
+///
+/// ```ignore
+/// piece_subset_coord! {
+///     /// Coordinate for the positions and permutation of the E slice edges
+///     ESliceEdgePermCoord, edges: E_SLICE_EDGES, solved: 10200
+/// }
+/// ```
+macro_rules! piece_subset_coord {
+    ($(#[$attr:meta])* $name:ident, corners: $subset:expr, solved: $solved:expr) => {
+        piece_subset_coord!(@coord $(#[$attr])* $name, corners, 8, $subset, $solved);
+    };
+    ($(#[$attr:meta])* $name:ident, edges: $subset:expr, solved: $solved:expr) => {
+        piece_subset_coord!(@coord $(#[$attr])* $name, edges, 12, $subset, $solved);
+    };
+    (@coord $(#[$attr:meta])* $name:ident, $pieces:ident, $num_positions:expr, $subset:expr, $solved:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name {
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                Self { }
+            }
+        }
+
+        impl $crate::coords::PieceCoordinate for $name {
+            const SIZE: usize = $crate::utils::coordutils::get_piece_subset_size($num_positions, $subset.len());
+            const SOLVED_COORDS: &'static [usize] = &[$solved];
+
+            fn pieces_to_coord(state: &$crate::rawcube::RawState) -> usize {
+                $crate::utils::coordutils::piece_subset_to_coord(state.$pieces.as_slice(), &$subset)
+            }
+
+            fn coord_to_pieces(coord: usize) -> $crate::rawcube::RawState {
+                let mut state = $crate::rawcube::RawState::solved();
+                let pieces = $crate::utils::coordutils::coord_to_piece_subset(coord, &$subset, state.$pieces.as_slice());
+                state.$pieces = $crate::rawcube::StateList::new(pieces);
+                state
+            }
+        }
+    };
+}
+pub(crate) use piece_subset_coord;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Algorithm;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::cp::CornerPermCoord;
+    use crate::coords::basic_coords::e_m_s_edges::{ESliceEdgePermCoord, MSliceEdgePermCoord, SSliceEdgePermCoord};
+    use crate::coords::basic_coords::e_slice_edge_sep::ESliceEdgeSepCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::coords::basic_coords::u_d_corner_perms::{UCornerPermCoord, DCornerPermCoord};
+    use crate::coords::phase_coords::dr::DRUDCoord;
+    use crate::coords::phase_coords::half_turn::{HalfTurnCoord, HalfTurnCornerCoord, HalfTurnEdgeCoord};
+    use crate::coords::phase_coords::htr::HTRCoord;
+
+    // Checks the constants of a coordinate against its conversions, and that turns move
+    // coordinates the same way they move states
+    fn check_coordinate<C: PieceCoordinate>(coord_type: C, alg: &str) {
+        let solved = RawState::solved();
+        assert!(coord_type.get_solved_coords().contains(&coord_type.convert_raw_state_to_coord(&solved)));
+        for coord in [0, C::SIZE / 3, C::SIZE - 1] {
+            let state = coord_type.convert_coord_to_example_raw_state(coord);
+            assert_eq!(coord_type.convert_raw_state_to_coord(&state), coord);
+        }
+
+        let turns = Turn::get_vec_from_alg_string(alg);
+        let turns = if C::WHOLE_COMPOUND_TURNS { turns } else { turns.to_base_turns() };
+        let mut state = solved;
+        let mut coord = coord_type.convert_raw_state_to_coord(&state);
+        for turn in turns {
+            state.apply_turn(&turn);
+            coord = coord_type.apply_turn(coord, &turn).unwrap();
+            assert!(coord < C::SIZE);
+            assert_eq!(coord, coord_type.convert_raw_state_to_coord(&state));
+        }
+    }
+
+    #[test]
+    fn test_piece_coordinates() {
+        let alg = "R U' F2 L D B' R2 U";
+        check_coordinate(EOFBCoord::new(), alg);
+        check_coordinate(COUDCoord::new(), alg);
+        check_coordinate(CornerPermCoord::new(), alg);
+        check_coordinate(ESliceEdgeSepCoord::new(), alg);
+        check_coordinate(UCornerPermCoord::new(), alg);
+        check_coordinate(DCornerPermCoord::new(), alg);
+        check_coordinate(ESliceEdgePermCoord::new(), alg);
+        check_coordinate(MSliceEdgePermCoord::new(), alg);
+        check_coordinate(SSliceEdgePermCoord::new(), alg);
+        check_coordinate(DRUDCoord::new(), alg);
+        check_coordinate(HTRCoord::new(), "U R2 D' F2 L2 B2");
+        let half_turn_alg = "R2 U2 F2 L2 D2 B2 R2";
+        check_coordinate(HalfTurnCornerCoord::new(), half_turn_alg);
+        check_coordinate(HalfTurnEdgeCoord::new(), half_turn_alg);
+        check_coordinate(HalfTurnCoord::new(), half_turn_alg);
+    }
+
+    #[test]
+    fn test_compound_turns() {
+        // Compound turns are only applied by coordinates defined within a subgroup
+        let r2 = Turn::from_name("R2");
+        assert!(EOFBCoord::new().apply_turn(0, &r2).is_err());
+        assert!(HalfTurnCoord::new().apply_turn(0, &r2).is_ok());
+
+        // Turns leaving the subgroup of a coordinate give an error
+        let r = Turn::from_name("R");
+        assert_eq!(HalfTurnCornerCoord::new().apply_turn(0, &r), Err(Error::LeavesSubgroup(r)));
+        assert_eq!(HalfTurnEdgeCoord::new().apply_turn(0, &r), Err(Error::LeavesSubgroup(r)));
+        assert_eq!(HTRCoord::new().apply_turn(0, &r), Err(Error::LeavesSubgroup(r)));
+    }
+}
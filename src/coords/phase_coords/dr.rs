@@ -0,0 +1,89 @@
+use crate::coords::{BasicCoordinate, PieceCoordinate};
+use crate::coords::basic_coords::eo_fb::EOFBCoord;
+use crate::coords::basic_coords::co_ud::COUDCoord;
+use crate::coords::basic_coords::e_slice_edge_sep::ESliceEdgeSepCoord;
+use crate::rawcube::RawState;
+use crate::moveset::MoveSet;
+
+
+const NUM_CORNER_TWIST_COMBINATIONS: usize = 2187;
+const NUM_EDGE_SEP_COMBINATIONS: usize = 495;
+
+
+/// Coordinate for domino reduction on the UD axis, for a cube with edges already oriented on the FB axis.
+/// Combines corner orientation on the UD axis with the separation of E slice edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DRUDCoord {
+}
+
+impl DRUDCoord {
+    pub fn new() -> Self {
+        Self { }
+    }
+
+    pub fn get_co_coord(coord: usize) -> usize {
+        coord / NUM_EDGE_SEP_COMBINATIONS
+    }
+
+    pub fn get_edge_sep_coord(coord: usize) -> usize {
+        coord % NUM_EDGE_SEP_COMBINATIONS
+    }
+}
+
+impl PieceCoordinate for DRUDCoord {
+    const SIZE: usize = NUM_CORNER_TWIST_COMBINATIONS * NUM_EDGE_SEP_COMBINATIONS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
+
+    fn pieces_to_coord(state: &RawState) -> usize {
+        let co_coord = COUDCoord::pieces_to_coord(state);
+        let edge_sep_coord = ESliceEdgeSepCoord::pieces_to_coord(state);
+        co_coord * NUM_EDGE_SEP_COMBINATIONS + edge_sep_coord
+    }
+
+    fn coord_to_pieces(coord: usize) -> RawState {
+        let mut state = COUDCoord::coord_to_pieces(Self::get_co_coord(coord));
+        state.edges = ESliceEdgeSepCoord::coord_to_pieces(Self::get_edge_sep_coord(coord)).edges;
+        state
+    }
+
+    fn get_allowed_turns() -> MoveSet {
+        // Only turns that keep edges oriented are allowed
+        MoveSet::eo_fb()
+    }
+}
+
+/// Checks whether a cube is in domino reduction on the UD axis
+pub fn is_in_dr(state: &RawState) -> bool {
+    DRUDCoord::new().convert_raw_state_to_coord(state) == 0
+        && EOFBCoord::new().convert_raw_state_to_coord(state) == 0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Turn;
+
+    fn get_state(alg: &str) -> RawState {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string(alg));
+        state
+    }
+
+    #[test]
+    fn test_is_in_dr() {
+        assert!(is_in_dr(&RawState::solved()));
+        assert!(is_in_dr(&get_state("U R2 D' F2 L2 U2 B2")));
+        assert!(!is_in_dr(&get_state("R")));
+        assert!(!is_in_dr(&get_state("F2 U F")));
+    }
+
+    #[test]
+    fn test_example_raw_state() {
+        let coord_type = DRUDCoord::new();
+        for coord in [0, 1, 494, 495, 500_000, 1_082_564] {
+            let state = coord_type.convert_coord_to_example_raw_state(coord);
+            assert_eq!(coord_type.convert_raw_state_to_coord(&state), coord);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use crate::coords::{BasicCoordinate, PieceCoordinate};
+use crate::coords::basic_coords::cp::CornerPermCoord;
+use crate::coords::basic_coords::e_m_s_edges::{E_SLICE_EDGES, M_SLICE_EDGES, S_SLICE_EDGES};
+use crate::coords::phase_coords::htr::get_half_turn_corner_perms;
+use crate::utils::coordutils::{coord_to_permutation, permutation_to_coord, permutation_to_coord_even_parity, is_even_parity};
+use crate::rawcube::{RawState, StateList, Corner, Edge};
+use crate::moveset::MoveSet;
+
+
+const NUM_HALF_TURN_CORNER_PERMS: usize = 96;
+const NUM_SLICE_EDGE_PERMUTATIONS: usize = 24;
+const NUM_HALF_TURN_EDGE_PERMS: usize = 6912; // 24 * 24 * 12. The parity of the S slice is fixed by the other two slices.
+
+// Positions of the edges in each slice, in the same order as the edges that belong there.
+const E_SLICE_POSITIONS: [Edge; 4] = E_SLICE_EDGES;
+const M_SLICE_POSITIONS: [Edge; 4] = M_SLICE_EDGES;
+const S_SLICE_POSITIONS: [Edge; 4] = S_SLICE_EDGES;
+
+
+/// Coordinate for the corner permutation of a cube in half turn reduction.
+/// Indexes the 96 corner permutations that can be reached using only half turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HalfTurnCornerCoord {
+}
+
+/// Coordinate for the edge permutation of a cube in half turn reduction.
+/// Every edge stays in its own slice, and the edges as a whole always have even parity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HalfTurnEdgeCoord {
+}
+
+/// Coordinate for the full state of a cube in half turn reduction, combining
+/// the corner and edge coordinates. There are 96 * 6912 = 663 552 positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HalfTurnCoord {
+}
+
+impl HalfTurnCornerCoord {
+    pub fn new() -> Self {
+        Self { }
+    }
+}
+
+impl HalfTurnEdgeCoord {
+    pub fn new() -> Self {
+        Self { }
+    }
+}
+
+impl HalfTurnCoord {
+    pub fn new() -> Self {
+        Self { }
+    }
+
+    pub fn get_corner_coord(coord: usize) -> usize {
+        coord / NUM_HALF_TURN_EDGE_PERMS
+    }
+
+    pub fn get_edge_coord(coord: usize) -> usize {
+        coord % NUM_HALF_TURN_EDGE_PERMS
+    }
+}
+
+// Half turns are applied as a whole, since a single quarter turn leaves the group
+impl PieceCoordinate for HalfTurnCornerCoord {
+    const SIZE: usize = NUM_HALF_TURN_CORNER_PERMS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
+    const WHOLE_COMPOUND_TURNS: bool = true;
+
+    // Corner permutations outside the group have no coordinate. Turns leaving the group are caught by
+    // is_in_domain, and other states must be checked before they are converted.
+    fn pieces_to_coord(state: &RawState) -> usize {
+        get_coord_for_corners(state.corners.as_slice())
+            .expect("Corner permutations outside the half turn group have no coordinate")
+    }
+
+    fn coord_to_pieces(coord: usize) -> RawState {
+        let mut state = RawState::solved();
+        state.corners = get_corners(coord);
+        state
+    }
+
+    fn get_allowed_turns() -> MoveSet {
+        MoveSet::half_turns()
+    }
+
+    fn is_in_domain(state: &RawState) -> bool {
+        get_coord_for_corners(state.corners.as_slice()).is_some()
+    }
+}
+
+impl PieceCoordinate for HalfTurnEdgeCoord {
+    const SIZE: usize = NUM_HALF_TURN_EDGE_PERMS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
+    const WHOLE_COMPOUND_TURNS: bool = true;
+
+    fn pieces_to_coord(state: &RawState) -> usize {
+        get_coord_for_edges(state.edges.as_slice())
+    }
+
+    fn coord_to_pieces(coord: usize) -> RawState {
+        let mut state = RawState::solved();
+        state.edges = get_edges(coord);
+        state
+    }
+
+    fn get_allowed_turns() -> MoveSet {
+        MoveSet::half_turns()
+    }
+
+    fn is_in_domain(state: &RawState) -> bool {
+        are_edges_in_slices(state.edges.as_slice())
+    }
+}
+
+impl PieceCoordinate for HalfTurnCoord {
+    const SIZE: usize = NUM_HALF_TURN_CORNER_PERMS * NUM_HALF_TURN_EDGE_PERMS;
+    const SOLVED_COORDS: &'static [usize] = &[0];
+    const WHOLE_COMPOUND_TURNS: bool = true;
+
+    fn pieces_to_coord(state: &RawState) -> usize {
+        let corner_coord = HalfTurnCornerCoord::pieces_to_coord(state);
+        let edge_coord = HalfTurnEdgeCoord::pieces_to_coord(state);
+        corner_coord * NUM_HALF_TURN_EDGE_PERMS + edge_coord
+    }
+
+    fn coord_to_pieces(coord: usize) -> RawState {
+        let mut state = RawState::solved();
+        state.corners = get_corners(Self::get_corner_coord(coord));
+        state.edges = get_edges(Self::get_edge_coord(coord));
+        state
+    }
+
+    fn get_allowed_turns() -> MoveSet {
+        MoveSet::half_turns()
+    }
+
+    fn is_in_domain(state: &RawState) -> bool {
+        is_in_half_turn_group(state)
+    }
+}
+
+/// Checks that every piece of a cube is where it can be solved using only half turns
+pub fn is_in_half_turn_group(state: &RawState) -> bool {
+    let cp_coord = CornerPermCoord::new().convert_raw_state_to_coord(state);
+    let corners_ok = get_half_turn_corner_perms().binary_search(&cp_coord).is_ok();
+    let twists_ok = state.twists.as_slice().iter().all(|twist| *twist as usize == 0);
+    let flips_ok = state.flips.as_slice().iter().all(|flip| *flip as usize == 0);
+    corners_ok && twists_ok && flips_ok && are_edges_in_slices(state.edges.as_slice())
+}
+
+// Checks that every edge is in the slice it belongs to
+fn are_edges_in_slices(edges: &[Edge]) -> bool {
+    let mut edges_ok = true;
+    for (positions, slice_edges) in [(E_SLICE_POSITIONS, E_SLICE_EDGES), (M_SLICE_POSITIONS, M_SLICE_EDGES), (S_SLICE_POSITIONS, S_SLICE_EDGES)] {
+        for position in positions {
+            edges_ok &= slice_edges.contains(&edges[position as usize]);
+        }
+    }
+    edges_ok
+}
+
+fn get_corners(coord: usize) -> StateList<Corner> {
+    let mut corners = Vec::with_capacity(8);
+    for corner in coord_to_permutation(get_half_turn_corner_perms()[coord], 8) {
+        corners.push(corner.into());
+    }
+    StateList::new(corners)
+}
+
+// Returns nothing for corner permutations that can not be reached using half turns
+fn get_coord_for_corners(corners: &[Corner]) -> Option<usize> {
+    let perm_coord = permutation_to_coord(corners);
+    get_half_turn_corner_perms().binary_search(&perm_coord).ok()
+}
+
+fn get_slice_perm(edges: &[Edge], positions: &[Edge; 4]) -> Vec<Edge> {
+    positions.iter().map(|position| edges[*position as usize]).collect()
+}
+
+fn get_coord_for_edges(edges: &[Edge]) -> usize {
+    let e_coord = permutation_to_coord(&get_slice_perm(edges, &E_SLICE_POSITIONS));
+    let m_coord = permutation_to_coord(&get_slice_perm(edges, &M_SLICE_POSITIONS));
+    let s_coord = permutation_to_coord_even_parity(&get_slice_perm(edges, &S_SLICE_POSITIONS));
+    (e_coord * NUM_SLICE_EDGE_PERMUTATIONS + m_coord) * (NUM_SLICE_EDGE_PERMUTATIONS / 2) + s_coord
+}
+
+fn get_edges(coord: usize) -> StateList<Edge> {
+    let s_coord = coord % (NUM_SLICE_EDGE_PERMUTATIONS / 2);
+    let m_coord = (coord / (NUM_SLICE_EDGE_PERMUTATIONS / 2)) % NUM_SLICE_EDGE_PERMUTATIONS;
+    let e_coord = coord / (NUM_SLICE_EDGE_PERMUTATIONS / 2) / NUM_SLICE_EDGE_PERMUTATIONS;
+
+    let e_perm = coord_to_permutation(e_coord, 4);
+    let m_perm = coord_to_permutation(m_coord, 4);
+
+    // The S slice coordinate ignores the order of the first two pieces, which is chosen
+    // so that the edges as a whole have even parity.
+    let mut s_perm = coord_to_permutation(s_coord * 2, 4);
+    if !(is_even_parity(&e_perm) ^ is_even_parity(&m_perm) ^ is_even_parity(&s_perm)) {
+        s_perm.swap(0, 1);
+    }
+
+    let mut edges = RawState::solved().edges;
+    for (slice_positions, slice_edges, perm) in [
+        (E_SLICE_POSITIONS, E_SLICE_EDGES, e_perm), (M_SLICE_POSITIONS, M_SLICE_EDGES, m_perm), (S_SLICE_POSITIONS, S_SLICE_EDGES, s_perm)
+    ] {
+        for (position, piece) in slice_positions.iter().zip(perm) {
+            edges.set(position, &slice_edges[piece]);
+        }
+    }
+    edges
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::Coordinate;
+    use crate::turndef::Turn;
+
+    fn get_state(alg: &str) -> RawState {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string(alg));
+        state
+    }
+
+    #[test]
+    fn test_solved_coord() {
+        let coord_type = HalfTurnCoord::new();
+        assert_eq!(coord_type.convert_raw_state_to_coord(&RawState::solved()), 0);
+        assert_ne!(coord_type.convert_raw_state_to_coord(&get_state("R2")), 0);
+    }
+
+    #[test]
+    fn test_example_raw_state() {
+        let coord_type = HalfTurnCoord::new();
+        for coord in [0, 1, 11, 12, 6911, 6912, 100_000, 663_551] {
+            let state = coord_type.convert_coord_to_example_raw_state(coord);
+            assert!(is_in_half_turn_group(&state));
+            let edges: Vec<usize> = state.edges.as_slice().iter().map(|edge| *edge as usize).collect();
+            assert!(is_even_parity(&edges));
+            assert_eq!(coord_type.convert_raw_state_to_coord(&state), coord);
+        }
+    }
+
+    #[test]
+    fn test_apply_turn() {
+        let alg = "R2 U2 F2 L2 D2 B2 R2 F2";
+        let mut coord = 0;
+        for turn in Turn::get_vec_from_alg_string(alg) {
+            coord = HalfTurnCoord::new().apply_turn(coord, &turn).unwrap();
+        }
+        assert_eq!(coord, HalfTurnCoord::new().convert_raw_state_to_coord(&get_state(alg)));
+    }
+
+    #[test]
+    fn test_is_in_half_turn_group() {
+        assert!(is_in_half_turn_group(&get_state("R2 U2 B2 L2")));
+        assert!(!is_in_half_turn_group(&get_state("R2 U")));
+        assert!(!is_in_half_turn_group(&get_state("F")));
+    }
+}
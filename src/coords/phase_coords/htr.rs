@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+
+use lazy_static::lazy_static;
+
+use crate::coords::{Coordinate, BasicCoordinate, PieceCoordinate};
+use crate::coords::basic_coords::cp::CornerPermCoord;
+use crate::coords::basic_coords::e_m_s_edges::{M_SLICE_EDGES, S_SLICE_EDGES};
+use crate::coords::phase_coords::dr::is_in_dr;
+use crate::utils::coordutils::{
+    coord_to_permutation, permutation_to_coord,
+    piece_distibution_to_coord, get_perm_for_distribution_coord
+};
+use crate::rawcube::{RawState, StateList, Corner, Edge};
+use crate::moveset::MoveSet;
+
+
+const NUM_CORNER_COSETS: usize = 420; // 8! / 96. Cosets of the corner permutations reachable with half turns.
+const NUM_MS_EDGE_SEPARATIONS: usize = 70; // 8 choose 4. Arrangements of M slice edges amongst the U and D layer positions.
+
+// Positions of the U and D layer edges, in the order used for the M/S separation.
+const UD_EDGE_POSITIONS: [Edge; 8] = [Edge::UB, Edge::UL, Edge::UF, Edge::UR, Edge::DB, Edge::DL, Edge::DF, Edge::DR];
+
+const HTR_SOLVED_COORD: usize = 20; // Corner coset 0, with the M slice edges in UB, UF, DB and DF
+
+
+/*
+The corners of a cube in half turn reduction can be solved using only half turns. This is the case
+exactly when the corner permutation is one of the 96 permutations reachable from solved using half turns.
+Every corner permutation belongs to one coset of this group, and since turns permute positions rather than
+pieces, relabelling the pieces of a permutation by an element of the group leaves the coset unchanged.
+*/
+struct CornerCosets {
+    coset_for_perm: Vec<usize>,
+    representative_perms: Vec<usize>,
+}
+
+lazy_static! {
+    static ref HALF_TURN_CORNER_PERMS: Vec<usize> = generate_half_turn_corner_perms();
+    static ref CORNER_COSETS: CornerCosets = generate_corner_cosets();
+}
+
+fn generate_half_turn_corner_perms() -> Vec<usize> {
+    let half_turns = MoveSet::half_turns();
+
+    let mut found = HashSet::new();
+    let mut frontier = vec![CornerPermCoord::new().convert_raw_state_to_coord(&RawState::solved())];
+    found.insert(frontier[0]);
+
+    while let Some(coord) = frontier.pop() {
+        for turn in &half_turns {
+            let mut new_coord = coord;
+            for base_turn in turn.to_base_turns() {
+                new_coord = CornerPermCoord::new().apply_turn(new_coord, &base_turn).expect("Half turns are made up of base turns");
+            }
+            if found.insert(new_coord) {
+                frontier.push(new_coord);
+            }
+        }
+    }
+
+    let mut perms: Vec<usize> = found.into_iter().collect();
+    perms.sort();
+    perms
+}
+
+fn generate_corner_cosets() -> CornerCosets {
+    let num_perms = CornerPermCoord::new().get_size();
+    let group: Vec<Vec<usize>> = HALF_TURN_CORNER_PERMS.iter()
+        .map(|coord| coord_to_permutation(*coord, 8))
+        .collect();
+
+    let mut coset_for_perm = vec![usize::MAX; num_perms];
+    let mut representative_perms = Vec::with_capacity(NUM_CORNER_COSETS);
+
+    for coord in 0..num_perms {
+        if coset_for_perm[coord] != usize::MAX {
+            continue;
+        }
+        let coset = representative_perms.len();
+        representative_perms.push(coord);
+
+        let perm = coord_to_permutation(coord, 8);
+        for relabelling in &group {
+            let relabelled: Vec<usize> = perm.iter().map(|piece| relabelling[*piece]).collect();
+            coset_for_perm[permutation_to_coord(&relabelled)] = coset;
+        }
+    }
+
+    CornerCosets {
+        coset_for_perm,
+        representative_perms,
+    }
+}
+
+/// The corner permutation coordinates reachable from solved using only half turns, in ascending order
+pub(crate) fn get_half_turn_corner_perms() -> &'static [usize] {
+    &HALF_TURN_CORNER_PERMS
+}
+
+/// Coordinate for half turn reduction, valid only for cubes in domino reduction on the UD axis.
+/// Combines the coset of the corner permutation with the separation of M and S slice edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HTRCoord {
+}
+
+impl HTRCoord {
+    pub fn new() -> Self {
+        Self { }
+    }
+}
+
+// Half turns pass through states outside of domino reduction, so the whole turn is applied to the
+// pieces before converting back to a coordinate
+impl PieceCoordinate for HTRCoord {
+    const SIZE: usize = NUM_CORNER_COSETS * NUM_MS_EDGE_SEPARATIONS;
+    const SOLVED_COORDS: &'static [usize] = &[HTR_SOLVED_COORD];
+    const WHOLE_COMPOUND_TURNS: bool = true;
+
+    fn pieces_to_coord(state: &RawState) -> usize {
+        get_coord_for_pieces(state.corners.as_slice(), state.edges.as_slice())
+    }
+
+    fn coord_to_pieces(coord: usize) -> RawState {
+        let mut state = RawState::solved();
+        state.corners = get_corners(coord);
+        state.edges = get_edges(coord);
+        state
+    }
+
+    fn get_allowed_turns() -> MoveSet {
+        // Only turns that stay within domino reduction are allowed
+        MoveSet::dr_ud()
+    }
+
+    fn is_in_domain(state: &RawState) -> bool {
+        is_in_dr(state)
+    }
+}
+
+fn get_corners(coord: usize) -> StateList<Corner> {
+    let coset = coord / NUM_MS_EDGE_SEPARATIONS;
+    let perm_coord = CORNER_COSETS.representative_perms[coset];
+
+    let mut corners = Vec::with_capacity(8);
+    for corner in coord_to_permutation(perm_coord, 8) {
+        corners.push(corner.into());
+    }
+    StateList::new(corners)
+}
+
+fn get_edges(coord: usize) -> StateList<Edge> {
+    let sep_coord = coord % NUM_MS_EDGE_SEPARATIONS;
+    let ud_edges = get_perm_for_distribution_coord(sep_coord, &M_SLICE_EDGES, &S_SLICE_EDGES);
+
+    let mut edges = RawState::solved().edges;
+    for (position, edge) in UD_EDGE_POSITIONS.iter().zip(ud_edges.iter()) {
+        edges.set(position, edge);
+    }
+    edges
+}
+
+fn get_coord_for_pieces(corners: &[Corner], edges: &[Edge]) -> usize {
+    let coset = CORNER_COSETS.coset_for_perm[permutation_to_coord(corners)];
+
+    let mut is_m_slice_edge = [false; 8];
+    for (i, position) in UD_EDGE_POSITIONS.iter().enumerate() {
+        is_m_slice_edge[i] = M_SLICE_EDGES.contains(&edges[*position as usize]);
+    }
+    let sep_coord = piece_distibution_to_coord(&is_m_slice_edge);
+
+    coset * NUM_MS_EDGE_SEPARATIONS + sep_coord
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Turn;
+
+    fn get_coord_after(alg: &str) -> usize {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string(alg));
+        HTRCoord::new().convert_raw_state_to_coord(&state)
+    }
+
+    #[test]
+    fn test_corner_cosets() {
+        assert_eq!(HALF_TURN_CORNER_PERMS.len(), 96);
+        assert_eq!(CORNER_COSETS.representative_perms.len(), NUM_CORNER_COSETS);
+    }
+
+    #[test]
+    fn test_solved_coord() {
+        assert_eq!(get_coord_after(""), HTR_SOLVED_COORD);
+        assert_eq!(get_coord_after("R2 U2 F2 L2 D2 B2"), HTR_SOLVED_COORD);
+        assert_ne!(get_coord_after("U"), HTR_SOLVED_COORD);
+        assert_ne!(get_coord_after("R2 U R2"), HTR_SOLVED_COORD);
+    }
+
+    #[test]
+    fn test_example_raw_state() {
+        let htr = HTRCoord::new();
+        for coord in [0, HTR_SOLVED_COORD, 1234, 29_399] {
+            let state = htr.convert_coord_to_example_raw_state(coord);
+            assert_eq!(htr.convert_raw_state_to_coord(&state), coord);
+        }
+    }
+
+    #[test]
+    fn test_apply_turn() {
+        for alg in ["U", "D'", "R2", "U R2 D F2"] {
+            let turns = Turn::get_vec_from_alg_string(alg);
+            let mut coord = HTR_SOLVED_COORD;
+            for turn in &turns {
+                coord = HTRCoord::new().apply_turn(coord, turn).unwrap();
+            }
+            assert_eq!(coord, get_coord_after(alg));
+        }
+    }
+}
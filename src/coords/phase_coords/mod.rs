@@ -0,0 +1,3 @@
+pub mod htr;
+pub mod half_turn;
+pub mod dr;
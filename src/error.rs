@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::tables::pruningtables::PruningEncoding;
+use crate::turndef::Turn;
+
+/// Errors from building tables or looking up turns in them, such as when a step is configured with
+/// turns its coordinate does not support
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Raw turn effects only exist for base turns of a single layer
+    CompoundTurn(Turn),
+    /// The move tables were not built for this turn
+    MissingMoveTable(Turn),
+    /// A search was given no coordinates to track
+    NoMoveTables,
+    /// A turn takes the pieces of a coordinate defined within a subgroup out of the subgroup
+    LeavesSubgroup(Turn),
+    /// A step was defined without any goal coordinates
+    NoStepGoals,
+    /// A step was asked to solve a state it can not be started from, such as DR before EO
+    CannotStartStep(String),
+    /// Distances in a Mod3 pruning table can only be found with the move tables it was built with
+    MissingMoveTables,
+    /// Some coordinates could not be reached from the solved coordinates within the maximum search distance
+    UnfilledPruningTable { num_filled: usize, size: usize },
+    /// A distance in a pruning table is too large for the encoding chosen for it
+    UnencodableDistance { distance: u8, encoding: PruningEncoding },
+    /// A goal coordinate of a pruning table is not a value of its coordinate
+    InvalidGoal { coord: usize, size: usize },
+    /// Table generation was cancelled through its cancellation token
+    Cancelled,
+    /// A turn moves two coordinates to the same coordinate
+    NotAPermutation { turn: Turn, coord: usize },
+    /// A turn followed by its inverse does not return to the coordinate it started from
+    InverseMismatch { turn: Turn, coord: usize },
+    /// A compound turn does not match the base turns it is made of
+    CompositionMismatch { turn: Turn, coord: usize },
+    /// A turn changes the distance in a pruning table by more than one
+    DistanceJump { turn: Turn, coord: usize },
+    /// A distance in a pruning table does not lead back to the solved coordinates
+    InconsistentDistance { coord: usize },
+    /// A table file or embedded table is truncated, corrupt, out of date or holds a different table
+    InvalidTableData(String),
+    /// A table file could not be opened or read
+    UnreadableTableFile(String),
+    /// A method description could not be read or is not valid
+    InvalidMethod(String),
+    /// A method that should solve every scramble found no solution
+    NoSolution,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::CompoundTurn(turn) => write!(f, "Raw turn effects are only available for base turns of a single layer, not {}. For compound turns, use move tables.", turn.to_name()),
+            Error::MissingMoveTable(turn) => write!(f, "Move table not found for turn {}", turn.to_name()),
+            Error::NoMoveTables => write!(f, "A search needs move tables for at least one coordinate"),
+            Error::LeavesSubgroup(turn) => write!(f, "Turn {} leaves the subgroup the coordinate is defined in", turn.to_name()),
+            Error::NoStepGoals => write!(f, "A step needs at least one goal coordinate"),
+            Error::CannotStartStep(step) => write!(f, "{} can not be started from this state", step),
+            Error::MissingMoveTables => write!(f, "Mod3 pruning tables need their move tables to find a distance"),
+            Error::UnfilledPruningTable { num_filled, size } => write!(f, "Pruning table generation failed. Only {} of {} coordinates were filled.", num_filled, size),
+            Error::UnencodableDistance { distance, encoding } => write!(f, "Distance {} can not be stored in a {:?} pruning table", distance, encoding),
+            Error::InvalidGoal { coord, size } => write!(f, "Goal coordinate {} is out of range for a coordinate with {} values", coord, size),
+            Error::Cancelled => write!(f, "Table generation was cancelled"),
+            Error::NotAPermutation { turn, coord } => write!(f, "Turn {} moves more than one coordinate to {}", turn.to_name(), coord),
+            Error::InverseMismatch { turn, coord } => write!(f, "Turn {} followed by its inverse does not return to {}", turn.to_name(), coord),
+            Error::CompositionMismatch { turn, coord } => write!(f, "Turn {} does not match its base turns from {}", turn.to_name(), coord),
+            Error::DistanceJump { turn, coord } => write!(f, "Turn {} changes the distance of {} by more than one", turn.to_name(), coord),
+            Error::InconsistentDistance { coord } => write!(f, "The distance of {} does not lead back to a solved coordinate", coord),
+            Error::InvalidTableData(reason) => write!(f, "Invalid table data: {}", reason),
+            Error::UnreadableTableFile(reason) => write!(f, "Could not read table file: {}", reason),
+            Error::InvalidMethod(reason) => write!(f, "Invalid method description: {}", reason),
+            Error::NoSolution => write!(f, "No solution was found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
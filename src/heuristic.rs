@@ -0,0 +1,226 @@
+use crate::error::Result;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::PruningTable;
+
+/*
+A heuristic gives a lower bound on the number of turns needed to solve a position of a search, which is 0 exactly
+when the position is solved. Positions are given as the coordinates the search tracks. For each position a
+heuristic keeps some distances, such as the exact distance of each of its pruning tables, and the distances of a
+position one turn away are found from them. This is what lets pruning tables storing distances modulo 3 be used,
+as they can only give a distance from the distance before the turn.
+*/
+pub trait Heuristic {
+    /// The number of distances kept for each position
+    fn get_num_distances(&self) -> usize;
+
+    /// Finds the distances of the position a search starts from
+    fn get_initial_distances(&self, coords: &[usize], distances: &mut [u8]) -> Result<()>;
+
+    /// Finds the distances of a position one turn away from a position with the previous distances
+    fn get_distances_after_turn(&self, coords: &[usize], previous_distances: &[u8], distances: &mut [u8]);
+
+    /// The lower bound on the number of turns left for a position with the given distances
+    fn estimate(&self, distances: &[u8]) -> u8;
+}
+
+/// The distance of one coordinate of the search, given by its pruning table
+pub struct TableHeuristic<'a> {
+    coord_index: usize,
+    move_tables: &'a MoveTables,
+    pruning_table: &'a PruningTable,
+}
+
+impl<'a> TableHeuristic<'a> {
+    /// Uses the pruning table for the coordinate at coord_index in the search, which is moved by the move tables
+    pub fn new(coord_index: usize, move_tables: &'a MoveTables, pruning_table: &'a PruningTable) -> Self {
+        Self {
+            coord_index,
+            move_tables,
+            pruning_table,
+        }
+    }
+}
+
+impl Heuristic for TableHeuristic<'_> {
+    fn get_num_distances(&self) -> usize {
+        1
+    }
+
+    fn get_initial_distances(&self, coords: &[usize], distances: &mut [u8]) -> Result<()> {
+        distances[0] = self.pruning_table.get_exact_distance(coords[self.coord_index], self.move_tables)?;
+        Ok(())
+    }
+
+    fn get_distances_after_turn(&self, coords: &[usize], previous_distances: &[u8], distances: &mut [u8]) {
+        distances[0] = self.pruning_table.get_distance_after_turn(coords[self.coord_index], previous_distances[0]);
+    }
+
+    fn estimate(&self, distances: &[u8]) -> u8 {
+        distances[0]
+    }
+}
+
+// Heuristics made of several parts, each keeping its own distances one after another
+struct Parts<'a> {
+    parts: Vec<Box<dyn Heuristic + 'a>>,
+}
+
+impl<'a> Parts<'a> {
+    fn get_num_distances(&self) -> usize {
+        self.parts.iter().map(|part| part.get_num_distances()).sum()
+    }
+
+    // Pairs each part with the range of the distances it keeps
+    fn iter(&self) -> impl Iterator<Item = (&(dyn Heuristic + 'a), std::ops::Range<usize>)> {
+        let mut start = 0;
+        self.parts.iter().map(move |part| {
+            let range = start..start + part.get_num_distances();
+            start = range.end;
+            (part.as_ref(), range)
+        })
+    }
+
+    fn get_initial_distances(&self, coords: &[usize], distances: &mut [u8]) -> Result<()> {
+        for (part, range) in self.iter() {
+            part.get_initial_distances(coords, &mut distances[range])?;
+        }
+        Ok(())
+    }
+
+    fn get_distances_after_turn(&self, coords: &[usize], previous_distances: &[u8], distances: &mut [u8]) {
+        for (part, range) in self.iter() {
+            part.get_distances_after_turn(coords, &previous_distances[range.clone()], &mut distances[range]);
+        }
+    }
+
+    fn estimates<'b>(&'b self, distances: &'b [u8]) -> impl Iterator<Item = u8> + 'b {
+        self.iter().map(move |(part, range)| part.estimate(&distances[range]))
+    }
+}
+
+/// The largest estimate of several heuristics, such as pruning tables for different coordinates that
+/// must all be solved
+pub struct MaxHeuristic<'a> {
+    parts: Parts<'a>,
+}
+
+impl<'a> MaxHeuristic<'a> {
+    pub fn new(parts: Vec<Box<dyn Heuristic + 'a>>) -> Self {
+        Self {
+            parts: Parts { parts },
+        }
+    }
+
+    /// Takes the largest distance of the pruning tables, where the table at each index is for the coordinate
+    /// at the same index in the search
+    pub fn from_tables(tables: &[(&'a MoveTables, &'a PruningTable)]) -> Self {
+        Self::new(tables.iter().enumerate()
+            .map(|(coord_index, (move_tables, pruning_table))| Box::new(TableHeuristic::new(coord_index, move_tables, pruning_table)) as Box<dyn Heuristic>)
+            .collect())
+    }
+}
+
+impl Heuristic for MaxHeuristic<'_> {
+    fn get_num_distances(&self) -> usize {
+        self.parts.get_num_distances()
+    }
+
+    fn get_initial_distances(&self, coords: &[usize], distances: &mut [u8]) -> Result<()> {
+        self.parts.get_initial_distances(coords, distances)
+    }
+
+    fn get_distances_after_turn(&self, coords: &[usize], previous_distances: &[u8], distances: &mut [u8]) {
+        self.parts.get_distances_after_turn(coords, previous_distances, distances);
+    }
+
+    fn estimate(&self, distances: &[u8]) -> u8 {
+        self.parts.estimates(distances).max().unwrap_or(0)
+    }
+}
+
+/// The sum of the estimates of several heuristics. This is only a lower bound when no turn can bring more
+/// than one of the parts closer to solved, such as when each part only counts turns the others never make.
+/// Otherwise searches using it may miss the shortest solutions. No step of the solver has parts like that yet,
+/// so it is only built for tests.
+#[cfg(test)]
+pub struct AdditiveHeuristic<'a> {
+    parts: Parts<'a>,
+}
+
+#[cfg(test)]
+impl<'a> AdditiveHeuristic<'a> {
+    pub fn new(parts: Vec<Box<dyn Heuristic + 'a>>) -> Self {
+        Self {
+            parts: Parts { parts },
+        }
+    }
+}
+
+#[cfg(test)]
+impl Heuristic for AdditiveHeuristic<'_> {
+    fn get_num_distances(&self) -> usize {
+        self.parts.get_num_distances()
+    }
+
+    fn get_initial_distances(&self, coords: &[usize], distances: &mut [u8]) -> Result<()> {
+        self.parts.get_initial_distances(coords, distances)
+    }
+
+    fn get_distances_after_turn(&self, coords: &[usize], previous_distances: &[u8], distances: &mut [u8]) {
+        self.parts.get_distances_after_turn(coords, previous_distances, distances);
+    }
+
+    fn estimate(&self, distances: &[u8]) -> u8 {
+        self.parts.estimates(distances).fold(0, u8::saturating_add)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::{Coordinate, BasicCoordinate};
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::rawcube::RawState;
+    use crate::tables::pruningtables::PruningEncoding;
+    use crate::turndef::Turn;
+
+    #[test]
+    fn test_combined_heuristics() {
+        let eo = EOFBCoord::new();
+        let co = COUDCoord::new();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let eo_pruning_table = PruningTable::new(eo, &eo_move_tables).unwrap();
+        let co_pruning_table = PruningTable::new_with_encoding(co, &co_move_tables, PruningEncoding::Mod3).unwrap();
+        let max = MaxHeuristic::from_tables(&[(&eo_move_tables, &eo_pruning_table), (&co_move_tables, &co_pruning_table)]);
+        let sum = AdditiveHeuristic::new(vec![
+            Box::new(TableHeuristic::new(0, &eo_move_tables, &eo_pruning_table)),
+            Box::new(TableHeuristic::new(1, &co_move_tables, &co_pruning_table)),
+        ]);
+        assert_eq!(max.get_num_distances(), 2);
+        assert_eq!(sum.get_num_distances(), 2);
+
+        // Following turns keeps the same distances as finding them from scratch
+        let mut state = RawState::solved();
+        let mut distances = vec![0; 2];
+        max.get_initial_distances(&[0, 0], &mut distances).unwrap();
+        assert_eq!(max.estimate(&distances), 0);
+        for turn in Turn::get_vec_from_alg_string("F R U' B L2 D F' R") {
+            state.apply_turn(&turn);
+            let coords = [eo.convert_raw_state_to_coord(&state), co.convert_raw_state_to_coord(&state)];
+            let previous_distances = distances.clone();
+            max.get_distances_after_turn(&coords, &previous_distances, &mut distances);
+
+            let mut initial_distances = vec![0; 2];
+            sum.get_initial_distances(&coords, &mut initial_distances).unwrap();
+            assert_eq!(distances, initial_distances);
+            let eo_distance = eo_pruning_table.get_distance(coords[0]).unwrap();
+            let co_distance = co_pruning_table.get_exact_distance(coords[1], &co_move_tables).unwrap();
+            assert_eq!(distances, [eo_distance, co_distance]);
+            assert_eq!(max.estimate(&distances), eo_distance.max(co_distance));
+            assert_eq!(sum.estimate(&distances), eo_distance + co_distance);
+        }
+    }
+}
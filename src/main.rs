@@ -1,17 +1,28 @@
+mod error;
 mod turndef;
+mod moveset;
 mod rawcube;
 mod coords;
 mod utils;
 mod tables;
 mod solver;
+mod heuristic;
+mod phases;
+mod rotations;
 
+use std::io::Write;
 use std::time::Instant;
 
 use crate::coords::{Coordinate, BasicCoordinate};
-use crate::tables::movetables::MoveTables;
-use crate::tables::pruningtables::PruningTable;
+use crate::error::Error;
+use crate::tables::progress::{CancellationToken, Progress};
+use crate::tables::registry::TableRegistry;
+use crate::tables::pruningtables::PruningEncoding;
 use crate::rawcube::RawState;
 use crate::solver::solve_optimally;
+use crate::turndef::{Turn, Algorithm};
+use crate::phases::dr_htr::{DRHTRSolver, DRHTRLimits};
+use crate::phases::config::MethodConfig;
 
 use crate::coords::basic_coords::eo_fb::EOFBCoord;
 use crate::coords::basic_coords::co_ud::COUDCoord;
@@ -21,6 +32,34 @@ use crate::coords::basic_coords::u_d_corner_perms::{UCornerPermCoord, DCornerPer
 use crate::coords::basic_coords::e_m_s_edges::{ESliceEdgePermCoord, MSliceEdgePermCoord, SSliceEdgePermCoord};
 
 
+// Draws a progress bar for a table on stderr, going to the next line once the table is finished
+fn show_progress(name: &str, progress: &Progress) {
+    const WIDTH: usize = 30;
+    let percent = (progress.filled * 100).checked_div(progress.total).unwrap_or(100);
+    let filled = percent * WIDTH / 100;
+    let mut line = format!("\r{} [{}{}] {:3}%", name, "#".repeat(filled), "-".repeat(WIDTH - filled), percent);
+    if let Some(depth) = progress.depth {
+        line += &format!(" depth {}", depth);
+    }
+    match progress.is_finished() {
+        true => line += &format!(" done in {:.2}s\n", progress.elapsed.as_secs_f64()),
+        false => if let Some(eta) = progress.get_eta() {
+            line += &format!(" ETA {:.0}s", eta.as_secs_f64());
+        },
+    }
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(line.as_bytes());
+    let _ = stderr.flush();
+}
+
+// Hands on a table, or reports why it could not be built and quits
+fn get_table_or_exit<T>(table: error::Result<T>) -> T {
+    table.unwrap_or_else(|error| {
+        eprintln!("Could not build tables: {}", error);
+        std::process::exit(if error == Error::Cancelled { 130 } else { 1 })
+    })
+}
+
 fn main() {
     // Get coordinate types
     let eo = EOFBCoord{};
@@ -45,46 +84,110 @@ fn main() {
     println!("M slice edges solved: {:?}", m_slice_edges.convert_raw_state_to_coord(&cube));
     println!("S slice edges solved: {:?}", s_slice_edges.convert_raw_state_to_coord(&cube));
 
+    let registry = TableRegistry::global();
+    registry.set_progress_callback(show_progress);
+    registry.set_cache_error_callback(|path, error| eprintln!("Could not cache table in {}: {}", path.display(), error));
+
+    // Ctrl-C stops generating tables, so no partly built table is saved. Searches can not be stopped,
+    // so a second Ctrl-C quits straight away.
+    let cancellation = CancellationToken::new();
+    registry.set_cancellation_token(cancellation.clone());
+    let handled = ctrlc::set_handler(move || match cancellation.is_cancelled() {
+        true => std::process::exit(130),
+        false => {
+            eprintln!("\nStopping, press Ctrl-C again to quit straight away");
+            cancellation.cancel();
+        },
+    });
+    if let Err(error) = handled {
+        eprintln!("Could not handle Ctrl-C: {}", error);
+    }
+
     // Generate move tables for EO
-    println!("Generating move tables");
-    let now = Instant::now();
-    let eo_move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns());
-    let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns());
-    // let e_slice_move_tables = MoveTables::new_basic_table(e_slice, &e_slice.get_allowed_turns());
-    let cp_move_tables = MoveTables::new_basic_table(cp, &cp.get_allowed_turns());
-    // let u_corners_move_tables = MoveTables::new_basic_table(u_corners, &u_corners.get_allowed_turns());
-    // let d_corners_move_tables = MoveTables::new_basic_table(d_corners, &d_corners.get_allowed_turns());
-    let e_slice_edges_move_tables = MoveTables::new_basic_table(e_slice_edges, &e_slice_edges.get_allowed_turns());
-    let m_slice_edges_move_tables = MoveTables::new_basic_table(m_slice_edges, &m_slice_edges.get_allowed_turns());
-    let s_slice_edges_move_tables = MoveTables::new_basic_table(s_slice_edges, &s_slice_edges.get_allowed_turns());
-    println!("Total time taken: {} seconds", (now.elapsed().as_micros() as f64 / 1_000_000.0));
+    let eo_move_tables = get_table_or_exit(registry.get_basic_move_tables(eo, &eo.get_allowed_turns()));
+    let co_move_tables = get_table_or_exit(registry.get_basic_move_tables(co, &co.get_allowed_turns()));
+    // let e_slice_move_tables = registry.get_basic_move_tables(e_slice, &e_slice.get_allowed_turns());
+    let cp_move_tables = get_table_or_exit(registry.get_basic_move_tables(cp, &cp.get_allowed_turns()));
+    // let u_corners_move_tables = registry.get_basic_move_tables(u_corners, &u_corners.get_allowed_turns());
+    // let d_corners_move_tables = registry.get_basic_move_tables(d_corners, &d_corners.get_allowed_turns());
+    let e_slice_edges_move_tables = get_table_or_exit(registry.get_basic_move_tables(e_slice_edges, &e_slice_edges.get_allowed_turns()));
+    let m_slice_edges_move_tables = get_table_or_exit(registry.get_basic_move_tables(m_slice_edges, &m_slice_edges.get_allowed_turns()));
+    let s_slice_edges_move_tables = get_table_or_exit(registry.get_basic_move_tables(s_slice_edges, &s_slice_edges.get_allowed_turns()));
 
     // Generate pruning tables for EO
-    println!("Generating pruning tables");
-    let now = Instant::now();
-    let eo_pruning_table = PruningTable::new(eo, &eo_move_tables);
-    let co_pruning_table = PruningTable::new(co, &co_move_tables);
-    // let e_slice_pruning_table = PruningTable::new(e_slice, &e_slice_move_tables);
-    let cp_pruning_table = PruningTable::new(cp, &cp_move_tables);
-    // let u_corners_pruning_table = PruningTable::new(u_corners, &u_corners_move_tables);
-    // let d_corners_pruning_table = PruningTable::new(d_corners, &d_corners_move_tables);
-    let e_slice_edges_pruning_table = PruningTable::new(e_slice_edges, &e_slice_edges_move_tables);
-    let m_slice_edges_pruning_table = PruningTable::new(m_slice_edges, &m_slice_edges_move_tables);
-    let s_slice_edges_pruning_table = PruningTable::new(s_slice_edges, &s_slice_edges_move_tables);
-    println!("Total time taken: {} seconds", (now.elapsed().as_micros() as f64 / 1_000_000.0));
-
-    // Sanity check pruning tables
-    println!("EO distance: {:?}", eo_pruning_table.get_distance(1));
-    println!("CO distance: {:?}", co_pruning_table.get_distance(1));
-    // println!("E slice distance: {:?}", e_slice_pruning_table.get_distance(1));
-    println!("CP distance: {:?}", cp_pruning_table.get_distance(1));
-    // println!("U corners distance: {:?}", u_corners_pruning_table.get_distance(1));
-    // println!("D corners distance: {:?}", d_corners_pruning_table.get_distance(1));
-    println!("E slice edges distance: {:?}", e_slice_edges_pruning_table.get_distance(1));
-    println!("M slice edges distance: {:?}", m_slice_edges_pruning_table.get_distance(1));
-    println!("S slice edges distance: {:?}", s_slice_edges_pruning_table.get_distance(1));
+    let eo_pruning_table = get_table_or_exit(registry.get_pruning_table(eo, &eo_move_tables, PruningEncoding::Byte));
+    let co_pruning_table = get_table_or_exit(registry.get_pruning_table(co, &co_move_tables, PruningEncoding::Byte));
+    // let e_slice_pruning_table = registry.get_pruning_table(e_slice, &e_slice_move_tables, PruningEncoding::Byte);
+    let cp_pruning_table = get_table_or_exit(registry.get_pruning_table(cp, &cp_move_tables, PruningEncoding::Byte));
+    // let u_corners_pruning_table = registry.get_pruning_table(u_corners, &u_corners_move_tables, PruningEncoding::Byte);
+    // let d_corners_pruning_table = registry.get_pruning_table(d_corners, &d_corners_move_tables, PruningEncoding::Byte);
+    let e_slice_edges_pruning_table = get_table_or_exit(registry.get_pruning_table(e_slice_edges, &e_slice_edges_move_tables, PruningEncoding::Byte));
+    let m_slice_edges_pruning_table = get_table_or_exit(registry.get_pruning_table(m_slice_edges, &m_slice_edges_move_tables, PruningEncoding::Byte));
+    let s_slice_edges_pruning_table = get_table_or_exit(registry.get_pruning_table(s_slice_edges, &s_slice_edges_move_tables, PruningEncoding::Byte));
+
+    // Distance distributions can be checked against published numbers
+    let pruning_tables = [
+        ("EO", &eo_move_tables, &eo_pruning_table),
+        ("CO", &co_move_tables, &co_pruning_table),
+        ("CP", &cp_move_tables, &cp_pruning_table),
+        ("E slice edges", &e_slice_edges_move_tables, &e_slice_edges_pruning_table),
+        ("M slice edges", &m_slice_edges_move_tables, &m_slice_edges_pruning_table),
+        ("S slice edges", &s_slice_edges_move_tables, &s_slice_edges_pruning_table),
+    ];
+    for (name, move_tables, pruning_table) in pruning_tables {
+        match pruning_table.get_stats(move_tables) {
+            Ok(stats) => println!("{} pruning table:\n{}", name, stats),
+            Err(error) => println!("{} pruning table statistics failed: {}", name, error),
+        }
+        if let Err(error) = move_tables.verify().and_then(|_| pruning_table.verify(move_tables)) {
+            println!("{} tables failed verification: {}", name, error);
+        }
+    }
 
     // let solution = solve_optimally(new_coord, eo_move_tables, eo_pruning_table);
     // println!("Solution: {:?}", solution.to_algorithm_string());
 
+    // Solve a scramble given on the command line with EO, DR, HTR and finish. With --niss, steps may be solved
+    // on the inverse. With --method, the steps are read from a method description file instead.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut niss = false;
+    let mut method_path = None;
+    let mut scramble = None;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--niss" => niss = true,
+            "--method" => method_path = args_iter.next(),
+            _ => scramble = Some(arg),
+        }
+    }
+
+    if let Some(scramble) = scramble {
+        let scramble = Turn::get_vec_from_alg_string(scramble);
+        println!("Scramble: {}", scramble.to_algorithm_string());
+        let now = Instant::now();
+        let solution = match method_path {
+            Some(path) => {
+                let method = match MethodConfig::load(path).and_then(|config| config.build()) {
+                    Ok(method) => method,
+                    Err(error) => {
+                        eprintln!("Could not use method {}: {}", path, error);
+                        std::process::exit(1);
+                    },
+                };
+                method.solve(&scramble)
+            },
+            None => {
+                let limits = DRHTRLimits { niss, ..DRHTRLimits::default() };
+                DRHTRSolver::new(&limits).and_then(|solver| solver.solve(&scramble)).map(Some)
+            },
+        };
+        match solution {
+            Ok(Some(solution)) => println!("{}", solution.to_annotated_string()),
+            Ok(None) => println!("No solution found"),
+            Err(error) => println!("Solving failed: {}", error),
+        }
+        println!("Total time taken: {} seconds", (now.elapsed().as_micros() as f64 / 1_000_000.0));
+    }
+
 }
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::turndef::{Turn, Algorithm};
+
+/*
+A move set is the set of turns a step or coordinate may use. Move sets are written with their generators,
+so <U, D, R2, L2, F2, B2> allows every turn of U and D, but only half turns of R, L, F and B.
+*/
+#[derive(Clone, Debug)]
+pub struct MoveSet {
+    turns: Vec<Turn>,
+}
+
+impl MoveSet {
+    pub fn new(turns: &[Turn]) -> Self {
+        let mut move_set = Self {
+            turns: Vec::new(),
+        };
+        for turn in turns {
+            if !move_set.turns.contains(turn) {
+                move_set.turns.push(*turn);
+            }
+        }
+        move_set
+    }
+
+    /// Builds the move set containing every power of each generator
+    pub fn from_generators(generators: &[Turn]) -> Self {
+        let mut turns = Vec::new();
+        for generator in generators {
+            turns.extend(generator.get_powers());
+        }
+        Self::new(&turns)
+    }
+
+    /// Every outer layer turn: <R, L, U, D, F, B>
+    pub fn full() -> Self {
+        Self::from_generators(&Turn::get_base_outer_layer_turns())
+    }
+
+    /// Every layer turn, including slices: <R, M, L, U, E, D, F, S, B>
+    pub fn full_with_slices() -> Self {
+        Self::from_generators(&Turn::get_base_layer_turns())
+    }
+
+    /// The turns that preserve edge orientation on the FB axis: <U, D, R, L, F2, B2>
+    pub fn eo_fb() -> Self {
+        Self::from_generators(&[Turn::UP, Turn::DOWN, Turn::RIGHT, Turn::LEFT, Turn::FRONT * 2, Turn::BACK * 2])
+    }
+
+    /// The turns that preserve domino reduction on the UD axis: <U, D, R2, L2, F2, B2>
+    pub fn dr_ud() -> Self {
+        Self::from_generators(&[Turn::UP, Turn::DOWN, Turn::RIGHT * 2, Turn::LEFT * 2, Turn::FRONT * 2, Turn::BACK * 2])
+    }
+
+    /// The turns that preserve domino reduction on the FB axis: <F, B, U2, D2, R2, L2>
+    pub fn dr_fb() -> Self {
+        Self::from_generators(&[Turn::FRONT, Turn::BACK, Turn::UP * 2, Turn::DOWN * 2, Turn::RIGHT * 2, Turn::LEFT * 2])
+    }
+
+    /// The turns that preserve domino reduction on the LR axis: <R, L, U2, D2, F2, B2>
+    pub fn dr_lr() -> Self {
+        Self::from_generators(&[Turn::RIGHT, Turn::LEFT, Turn::UP * 2, Turn::DOWN * 2, Turn::FRONT * 2, Turn::BACK * 2])
+    }
+
+    /// The turns that preserve half turn reduction: <U2, D2, R2, L2, F2, B2>
+    pub fn half_turns() -> Self {
+        let generators: Vec<Turn> = Turn::get_base_outer_layer_turns().iter().map(|turn| *turn * 2).collect();
+        Self::from_generators(&generators)
+    }
+
+    /// Two generator move set: <R, U>
+    pub fn ru() -> Self {
+        Self::from_generators(&[Turn::RIGHT, Turn::UP])
+    }
+
+    /// Roux last six edges move set: <M, U>
+    pub fn mu() -> Self {
+        Self::from_generators(&[Turn::MIDDLE, Turn::UP])
+    }
+
+    pub fn get_turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    pub fn union(&self, other: &MoveSet) -> MoveSet {
+        let mut turns = self.turns.clone();
+        turns.extend(other.turns.iter());
+        Self::new(&turns)
+    }
+
+    /// Whether every turn of the algorithm is in the move set
+    pub fn contains_algorithm(&self, alg: &[Turn]) -> bool {
+        alg.iter().all(|turn| self.turns.contains(turn))
+    }
+
+    /// Whether every turn of the other move set is in this one
+    pub fn is_subset_of(&self, other: &MoveSet) -> bool {
+        other.contains_algorithm(&self.turns)
+    }
+}
+
+// Move sets are equal if they have the same turns, in any order
+impl PartialEq for MoveSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.turns.len() == other.turns.len() && self.is_subset_of(other)
+    }
+}
+
+impl Eq for MoveSet {}
+
+impl Hash for MoveSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut turns = self.turns.clone();
+        turns.sort();
+        turns.hash(state);
+    }
+}
+
+impl Deref for MoveSet {
+    type Target = [Turn];
+
+    fn deref(&self) -> &[Turn] {
+        &self.turns
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveSet {
+    type Item = &'a Turn;
+    type IntoIter = std::slice::Iter<'a, Turn>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.turns.iter()
+    }
+}
+
+impl FromStr for MoveSet {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let inner = text.trim().strip_prefix('<').and_then(|text| text.strip_suffix('>'))
+            .ok_or_else(|| format!("Move sets must be written in angle brackets: {:?}", text))?;
+
+        let mut generators = Vec::new();
+        for name in inner.split(',').map(|name| name.trim()).filter(|name| !name.is_empty()) {
+            generators.push(Turn::try_from_name(name).ok_or_else(|| format!("Invalid turn: {:?}", name))?);
+        }
+        Ok(Self::from_generators(&generators))
+    }
+}
+
+// Writes each layer as a single generator when every turn of it is in the set, such as U for U, U2 and U'
+impl fmt::Display for MoveSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut generators: Vec<Turn> = Vec::new();
+        for turn in &self.turns {
+            if generators.iter().any(|generator| generator.get_powers().contains(turn)) {
+                continue;
+            }
+            let powers = turn.get_powers();
+            if powers.len() == 3 && self.contains_algorithm(&powers) {
+                let generator = powers.iter().find(|power| !power.to_name().ends_with(['2', '\''])).unwrap_or(turn);
+                generators.push(*generator);
+            } else {
+                generators.push(*turn);
+            }
+        }
+        write!(f, "<{}>", generators.to_algorithm_string().replace(' ', ", "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let move_set: MoveSet = "<U, D, R2, L2, F2, B2>".parse().unwrap();
+        assert_eq!(move_set, MoveSet::dr_ud());
+        assert_eq!(move_set.len(), 10);
+        assert_eq!(move_set.to_string(), "<U, D, R2, L2, F2, B2>");
+        assert_eq!("<R, U>".parse::<MoveSet>().unwrap(), MoveSet::ru());
+        assert_eq!("<L, R, U, D, F, B>".parse::<MoveSet>().unwrap().len(), 18);
+        assert!("U, D".parse::<MoveSet>().is_err());
+        assert!("<U, X>".parse::<MoveSet>().is_err());
+    }
+
+    #[test]
+    fn test_predefined_sets() {
+        assert_eq!(MoveSet::full().len(), 18);
+        assert_eq!(MoveSet::full_with_slices().len(), 27);
+        assert_eq!(MoveSet::eo_fb().len(), 14);
+        assert_eq!(MoveSet::half_turns().len(), 6);
+        assert_eq!(MoveSet::mu().to_string(), "<M, U>");
+        for dr in [MoveSet::dr_ud(), MoveSet::dr_fb(), MoveSet::dr_lr()] {
+            assert_eq!(dr.len(), 10);
+            assert!(MoveSet::half_turns().is_subset_of(&dr));
+            assert!(dr.is_subset_of(&MoveSet::full()));
+        }
+    }
+
+    #[test]
+    fn test_union_and_contains() {
+        let union = MoveSet::ru().union(&MoveSet::half_turns());
+        assert_eq!(union.len(), 10);
+        assert_eq!(union.to_string(), "<R, U, L2, D2, F2, B2>");
+        assert!(union.contains_algorithm(&Turn::get_vec_from_alg_string("R U' R' F2 U2")));
+        assert!(!union.contains_algorithm(&Turn::get_vec_from_alg_string("R U F")));
+        assert!(MoveSet::dr_ud().contains(&Turn::from_name("D'")));
+    }
+}
@@ -0,0 +1,464 @@
+use std::fs;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::{self, Error};
+use crate::coords::basic_coords::eo_fb::EOFBCoord;
+use crate::coords::basic_coords::co_ud::COUDCoord;
+use crate::coords::basic_coords::piece_subset::PieceSubsetCoord;
+use crate::coords::phase_coords::dr::DRUDCoord;
+use crate::coords::phase_coords::htr::HTRCoord;
+use crate::coords::phase_coords::half_turn::HalfTurnCoord;
+use crate::moveset::MoveSet;
+use crate::rawcube::{Corner, Edge};
+use crate::phases::eo::EOSolver;
+use crate::phases::dr::DRSolver;
+use crate::phases::htr::HTRSolver;
+use crate::phases::finish::FinishSolver;
+use crate::phases::dr_htr::{solve_in_orientations, get_orientation_axes};
+use crate::phases::method::{Method, StepLimits};
+use crate::phases::solution::MultiStepSolution;
+use crate::phases::step::{Step, CoordinateStep, StepGoal};
+use crate::tables::pruningtables::GoalSet;
+use crate::rotations::{get_eo_dr_orientations, get_all_orientations};
+use crate::turndef::Turn;
+
+/*
+Methods are described as a list of steps separated by "->", for example:
+
+    eo(any, niss) -> dr(ud, max=10) -> htr(<U, D, R2, L2, F2, B2>) -> finish
+
+Besides eo, dr, htr and finish, co orients the corners on the DR axis, for methods that do it as its own step.
+pieces solves the corners and edges listed in its options, such as pieces(DFR, FR, DR, co, eo) for a pair and
+an edge, where co and eo also orient the listed corners and edges. Pieces are named as seen with EO on the FB
+axis and DR on the UD axis, so a method that allows several axes solves the matching pieces of each orientation.
+With any, the pieces only need to be built together somewhere on the cube, as when blockbuilding.
+
+Each step may be followed by a list of options in brackets:
+    - An axis for eo and dr steps: any, ud, fb or lr. The default is any. EO and DR can not be on the same axis.
+    - A move set in angle brackets, such as <U, D, R2, L2, F2, B2>. The default is every turn the step can use.
+    - slack=N keeps solutions up to N turns longer than optimal. The default is 1, or 0 for finish.
+    - max=N keeps solutions of at most N turns.
+    - count=N tries at most N solutions of the step. The default is 4, or every solution for finish.
+    - niss also searches for the step on the inverse scramble.
+Lines starting with # are comments.
+*/
+
+const AXES: [&str; 3] = ["ud", "fb", "lr"];
+
+// The largest pieces step allowed, as its pruning table takes a byte per coordinate
+const MAX_PIECES_SIZE: usize = 1 << 28;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepKind {
+    Eo,
+    Dr,
+    Htr,
+    Finish,
+    Co,
+    Pieces { coord_type: PieceSubsetCoord, anywhere: bool },
+}
+
+/// A step of a method description, before its tables are built
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepConfig {
+    pub kind: StepKind,
+    pub axis: Option<&'static str>,
+    pub move_set: Option<MoveSet>,
+    pub limits: StepLimits,
+}
+
+/// A parsed method description
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodConfig {
+    pub steps: Vec<StepConfig>,
+}
+
+/// A method built from a description, along with the orientations it may be solved in
+pub struct ConfiguredMethod {
+    method: Method,
+    rotations: Vec<Vec<Turn>>,
+}
+
+impl StepKind {
+    fn from_name(name: &str) -> error::Result<Self> {
+        match name {
+            "eo" => Ok(StepKind::Eo),
+            "dr" => Ok(StepKind::Dr),
+            "htr" => Ok(StepKind::Htr),
+            "finish" => Ok(StepKind::Finish),
+            "co" => Ok(StepKind::Co),
+            "pieces" => Ok(StepKind::Pieces { coord_type: PieceSubsetCoord::new(&[], &[]), anywhere: false }),
+            _ => Err(Error::InvalidMethod(format!("Unknown step: {:?}", name))),
+        }
+    }
+
+    // The name of the step in a method description
+    fn get_name(&self) -> &'static str {
+        match self {
+            StepKind::Eo => "eo",
+            StepKind::Dr => "dr",
+            StepKind::Htr => "htr",
+            StepKind::Finish => "finish",
+            StepKind::Co => "co",
+            StepKind::Pieces { .. } => "pieces",
+        }
+    }
+
+    fn get_default_turns(&self) -> MoveSet {
+        match self {
+            StepKind::Eo => EOFBCoord::new().get_allowed_turns(),
+            StepKind::Dr => DRUDCoord::new().get_allowed_turns(),
+            StepKind::Htr => HTRCoord::new().get_allowed_turns(),
+            StepKind::Finish => HalfTurnCoord::new().get_allowed_turns(),
+            StepKind::Co => COUDCoord::new().get_allowed_turns(),
+            StepKind::Pieces { coord_type, .. } => coord_type.get_allowed_turns(),
+        }
+    }
+
+    fn get_default_limits(&self) -> StepLimits {
+        match self {
+            StepKind::Finish => StepLimits { slack: 0, max_solutions: usize::MAX, ..StepLimits::default() },
+            _ => StepLimits::default(),
+        }
+    }
+}
+
+impl StepConfig {
+    fn parse(text: &str) -> error::Result<Self> {
+        let text = text.trim();
+        let (name, options) = match text.find('(') {
+            Some(start) => {
+                let options = text[start + 1..].strip_suffix(')')
+                    .ok_or_else(|| Error::InvalidMethod(format!("Missing closing bracket in step: {:?}", text)))?;
+                (text[..start].trim(), options)
+            },
+            None => (text, ""),
+        };
+
+        let kind = StepKind::from_name(name)?;
+        let mut step = Self {
+            kind,
+            axis: None,
+            move_set: None,
+            limits: kind.get_default_limits(),
+        };
+        for option in split_options(options) {
+            step.parse_option(option)?;
+        }
+        if let StepKind::Pieces { coord_type, .. } = step.kind {
+            if coord_type.get_corners().is_empty() && coord_type.get_edges().is_empty() {
+                return Err(Error::InvalidMethod("A pieces step needs at least one corner or edge".to_string()));
+            }
+            if coord_type.get_size() > MAX_PIECES_SIZE {
+                return Err(Error::InvalidMethod(format!("Too many pieces to solve in one step: {:?}", text)));
+            }
+        }
+        Ok(step)
+    }
+
+    fn parse_option(&mut self, option: &str) -> error::Result<()> {
+        if option.starts_with('<') {
+            self.move_set = Some(self.parse_move_set(option)?);
+            return Ok(());
+        }
+
+        match option.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim();
+                let value = value.trim();
+                match key {
+                    "slack" => self.limits.slack = parse_value(key, value)?,
+                    "max" => self.limits.max_length = parse_value(key, value)?,
+                    "count" => self.limits.max_solutions = parse_value(key, value)?,
+                    "niss" => self.limits.niss = parse_value(key, value)?,
+                    _ => return Err(Error::InvalidMethod(format!("Unknown option: {:?}", key))),
+                }
+            },
+            None => {
+                let has_axis = matches!(self.kind, StepKind::Eo | StepKind::Dr);
+                if option == "niss" {
+                    self.limits.niss = true;
+                } else if let StepKind::Pieces { coord_type, anywhere } = self.kind {
+                    self.kind = match option {
+                        "any" => StepKind::Pieces { coord_type, anywhere: true },
+                        _ => StepKind::Pieces { coord_type: parse_piece(coord_type, option)?, anywhere },
+                    };
+                } else if has_axis && option == "any" {
+                    self.axis = None;
+                } else if let Some(axis) = AXES.iter().find(|axis| has_axis && **axis == option) {
+                    self.axis = Some(axis);
+                } else {
+                    return Err(Error::InvalidMethod(format!("Unknown option: {:?}", option)));
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn parse_move_set(&self, text: &str) -> error::Result<MoveSet> {
+        let move_set: MoveSet = text.parse().map_err(Error::InvalidMethod)?;
+        if !move_set.is_subset_of(&self.kind.get_default_turns()) {
+            return Err(Error::InvalidMethod(format!("{} can not be used in {}", move_set, self.kind.get_name())));
+        }
+        Ok(move_set)
+    }
+
+    fn build(&self) -> error::Result<Box<dyn Step>> {
+        let move_set = self.move_set.clone().unwrap_or_else(|| self.kind.get_default_turns());
+        let step: Box<dyn Step> = match self.kind {
+            StepKind::Eo => Box::new(EOSolver::new_with_turns(&move_set)?),
+            StepKind::Dr => Box::new(DRSolver::new_with_turns(&move_set)?),
+            StepKind::Htr => Box::new(HTRSolver::new_with_turns(&move_set)?),
+            StepKind::Finish => Box::new(FinishSolver::new_with_turns(&move_set)?),
+            StepKind::Co => Box::new(CoordinateStep::new("CO", vec![StepGoal::new_basic(COUDCoord::new(), &move_set)?])?),
+            StepKind::Pieces { coord_type, anywhere: false } => Box::new(CoordinateStep::new("Pieces", vec![StepGoal::new_basic(coord_type, &move_set)?])?),
+            StepKind::Pieces { coord_type, anywhere: true } => {
+                let goal = StepGoal::new_basic_with_goals(coord_type, &move_set, "anywhere", &get_block_goals(coord_type))?;
+                Box::new(CoordinateStep::new("Pieces", vec![goal])?)
+            },
+        };
+        Ok(step)
+    }
+}
+
+impl MethodConfig {
+    pub fn parse(text: &str) -> error::Result<Self> {
+        let description: Vec<&str> = text.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        let description = description.join(" ");
+
+        let mut steps = Vec::new();
+        for step in description.split("->") {
+            steps.push(StepConfig::parse(step)?);
+        }
+        let config = Self {
+            steps,
+        };
+
+        // DR on an axis keeps the edges oriented on the other two axes, so EO must be on one of them
+        if let (Some(eo_axis), Some(dr_axis)) = (config.get_axis(StepKind::Eo), config.get_axis(StepKind::Dr)) {
+            if eo_axis == dr_axis {
+                return Err(Error::InvalidMethod(format!("EO on the {} axis can not be followed by DR on the same axis", eo_axis)));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &str) -> error::Result<Self> {
+        let text = fs::read_to_string(path).map_err(|error| Error::InvalidMethod(format!("Could not read {}: {}", path, error)))?;
+        Self::parse(&text)
+    }
+
+    /// Builds the tables for every step
+    pub fn build(&self) -> error::Result<ConfiguredMethod> {
+        let mut method = Method::new();
+        for step in &self.steps {
+            method.push_step(step.build()?, step.limits);
+        }
+        Ok(ConfiguredMethod {
+            method,
+            rotations: self.get_rotations(),
+        })
+    }
+
+    // The axis chosen for the first step of a kind, or nothing if any axis is allowed
+    fn get_axis(&self, kind: StepKind) -> Option<&'static str> {
+        self.steps.iter().find(|step| step.kind == kind).and_then(|step| step.axis)
+    }
+
+    // Returns the orientations matching the axes chosen for EO and DR
+    fn get_rotations(&self) -> Vec<Vec<Turn>> {
+        let eo_axis = self.get_axis(StepKind::Eo);
+        let dr_axis = self.get_axis(StepKind::Dr);
+
+        get_eo_dr_orientations().into_iter().filter(|rotation| {
+            let (rotation_eo_axis, rotation_dr_axis) = get_orientation_axes(rotation);
+            eo_axis.is_none_or(|axis| axis == rotation_eo_axis) && dr_axis.is_none_or(|axis| axis == rotation_dr_axis)
+        }).collect()
+    }
+}
+
+impl ConfiguredMethod {
+    /// Returns the shortest solution over every allowed orientation, or nothing if the method could not solve the scramble
+    pub fn solve(&self, scramble: &[Turn]) -> error::Result<Option<MultiStepSolution>> {
+        solve_in_orientations(&self.method, scramble, &self.rotations)
+    }
+}
+
+// Splits on commas outside of move sets
+fn split_options(options: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, character) in options.char_indices() {
+        match character {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(options[start..i].trim());
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(options[start..].trim());
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+// The coordinates with the pieces built together somewhere on the cube, which is when some rotation of the whole
+// cube brings them to where they are solved
+fn get_block_goals(coord_type: PieceSubsetCoord) -> GoalSet {
+    let solved = coord_type.get_solved_coords();
+    get_all_orientations().iter()
+        .map(|rotation| GoalSet::from_raw_state_predicate(coord_type, |state| {
+            let mut rotated = state.clone();
+            rotated.apply_algorithm(rotation);
+            solved.contains(&coord_type.convert_raw_state_to_coord(&rotated))
+        }))
+        .fold(GoalSet::from_coords(&[]), |goals, rotation_goals| goals.union(&rotation_goals))
+}
+
+// Adds a corner or edge named in a pieces step to the pieces it solves, or their orientation for co and eo
+fn parse_piece(coord_type: PieceSubsetCoord, name: &str) -> error::Result<PieceSubsetCoord> {
+    let corner = (0..8).map(Into::<Corner>::into).find(|corner| format!("{:?}", corner).eq_ignore_ascii_case(name));
+    let edge = (0..12).map(Into::<Edge>::into).find(|edge| format!("{:?}", edge).eq_ignore_ascii_case(name));
+    match (name, corner, edge) {
+        ("co", _, _) => Ok(coord_type.with_corner_orientation()),
+        ("eo", _, _) => Ok(coord_type.with_edge_orientation()),
+        (_, Some(corner), _) => Ok(coord_type.with_corner(corner)),
+        (_, _, Some(edge)) => Ok(coord_type.with_edge(edge)),
+        _ => Err(Error::InvalidMethod(format!("Unknown piece: {:?}", name))),
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> error::Result<T> {
+    value.parse().map_err(|_| Error::InvalidMethod(format!("Invalid value for {}: {:?}", key, value)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rawcube::RawState;
+    use crate::rotations::rotate_algorithm;
+
+    #[test]
+    fn test_parse() {
+        let config = MethodConfig::parse("# A DR method\neo(any, niss) -> dr(ud, max=10, count=2)\n  -> htr(<U, D, R2, L2, F2, B2>, slack=0) -> finish").unwrap();
+        assert_eq!(config.steps.len(), 4);
+        assert_eq!(config.steps[0].kind, StepKind::Eo);
+        assert_eq!(config.steps[0].axis, None);
+        assert!(config.steps[0].limits.niss);
+        assert_eq!(config.steps[1].axis, Some("ud"));
+        assert_eq!(config.steps[1].limits.max_length, 10);
+        assert_eq!(config.steps[1].limits.max_solutions, 2);
+        assert_eq!(config.steps[2].move_set, Some(MoveSet::dr_ud()));
+        assert_eq!(config.steps[2].limits.slack, 0);
+        assert_eq!(config.steps[3].kind, StepKind::Finish);
+        assert_eq!(config.steps[3].limits.max_solutions, usize::MAX);
+
+        // EO on any axis with DR on UD leaves the two orientations with EO on FB or LR
+        assert_eq!(config.get_rotations().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(MethodConfig::parse("eo -> cross"), Err(Error::InvalidMethod(_))));
+        assert!(MethodConfig::parse("eo(diagonal)").is_err());
+        assert!(MethodConfig::parse("htr(ud)").is_err());
+        assert!(MethodConfig::parse("dr(max=ten)").is_err());
+        assert!(MethodConfig::parse("dr(<U, D, X>)").is_err());
+        assert!(MethodConfig::parse("htr(<U, R>)").is_err());
+        assert!(MethodConfig::parse("eo(fb").is_err());
+        assert!(MethodConfig::parse("eo(ud) -> dr(ud)").is_err());
+        assert!(MethodConfig::parse("eo(fb) -> dr(ud)").is_ok());
+        assert!(matches!(MethodConfig::load("methods/missing.txt"), Err(Error::InvalidMethod(_))));
+        assert!(MethodConfig::parse("pieces").is_err());
+        assert!(MethodConfig::parse("pieces(UF, UFX)").is_err());
+        assert!(MethodConfig::parse("pieces(UB, UL, UF, UR, DB, DL, DF, DR, eo)").is_err());
+        assert!(MethodConfig::parse("eo(ud)").is_ok());
+    }
+
+    #[test]
+    fn test_coordinate_steps() {
+        // Corners are oriented as their own step, keeping the edges oriented
+        let method = MethodConfig::parse("eo(fb, count=1) -> co(<U, D, R, L, F2, B2>, count=1)").unwrap().build().unwrap();
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let solution = method.solve(&scramble).unwrap().unwrap();
+        assert_eq!(solution.steps[1].name, "CO");
+
+        // The corners are oriented on the UD axis of one of the orientations with EO on the FB axis
+        let mut alg = scramble.clone();
+        alg.extend(solution.get_turns());
+        assert!(method.rotations.iter().any(|rotation| {
+            let mut state = RawState::solved();
+            state.apply_algorithm(&rotate_algorithm(&alg, rotation));
+            EOFBCoord::new().convert_raw_state_to_coord(&state) == 0 && COUDCoord::new().convert_raw_state_to_coord(&state) == 0
+        }));
+    }
+
+    #[test]
+    fn test_pieces_step() {
+        let config = MethodConfig::parse("pieces(DFR, fr, DR, co, eo, count=1)").unwrap();
+        let block = PieceSubsetCoord::new(&[Corner::DFR], &[Edge::FR, Edge::DR]).with_corner_orientation().with_edge_orientation();
+        assert_eq!(config.steps[0].kind, StepKind::Pieces { coord_type: block, anywhere: false });
+        assert_eq!(config.steps[0].limits.max_solutions, 1);
+
+        // The pair and edge are solved in one of the orientations
+        let method = config.build().unwrap();
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let solution = method.solve(&scramble).unwrap().unwrap();
+        assert_eq!(solution.steps[0].name, "Pieces");
+        let mut alg = scramble.clone();
+        alg.extend(solution.get_turns());
+        assert!(method.rotations.iter().any(|rotation| {
+            let mut state = RawState::solved();
+            state.apply_algorithm(&rotate_algorithm(&alg, rotation));
+            block.convert_raw_state_to_coord(&state) == block.get_solved_coords()[0]
+        }));
+    }
+
+    #[test]
+    fn test_pieces_anywhere() {
+        // A pair built anywhere is never further away than the pair built in place
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let in_place = MethodConfig::parse("pieces(DFR, FR, co, eo, count=1)").unwrap().build().unwrap().solve(&scramble).unwrap().unwrap();
+        let config = MethodConfig::parse("pieces(DFR, FR, co, eo, any, count=1)").unwrap();
+        let method = config.build().unwrap();
+        let anywhere = method.solve(&scramble).unwrap().unwrap();
+        assert!(anywhere.len() <= in_place.len());
+
+        // Some rotation of the whole cube brings the built pair to where it is solved
+        let StepKind::Pieces { coord_type, anywhere: true } = config.steps[0].kind else {
+            panic!("Expected a pieces step built anywhere");
+        };
+        let mut state = RawState::solved();
+        state.apply_algorithm(&scramble);
+        state.apply_algorithm(&anywhere.get_turns());
+        assert!(get_all_orientations().iter().any(|rotation| {
+            let mut rotated = state.clone();
+            rotated.apply_algorithm(rotation);
+            coord_type.convert_raw_state_to_coord(&rotated) == coord_type.get_solved_coords()[0]
+        }));
+    }
+
+    #[test]
+    fn test_solve() {
+        let method = MethodConfig::parse("eo(lr, max=6)").unwrap().build().unwrap();
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let solution = method.solve(&scramble).unwrap().unwrap();
+        assert_eq!(solution.steps[0].name, "EO-lr");
+        assert!(solution.len() <= 6);
+
+        // Rotating the LR axis to FB gives edges oriented on the FB axis
+        let rotation = &method.rotations[0];
+        assert_eq!(get_orientation_axes(rotation).0, "lr");
+        let mut alg = scramble.clone();
+        alg.extend(solution.get_turns());
+        let mut state = RawState::solved();
+        state.apply_algorithm(&rotate_algorithm(&alg, rotation));
+        assert_eq!(EOFBCoord::new().convert_raw_state_to_coord(&state), 0);
+    }
+}
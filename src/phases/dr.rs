@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::{Error, Result};
+use crate::coords::basic_coords::eo_fb::EOFBCoord;
+use crate::coords::basic_coords::co_ud::COUDCoord;
+use crate::coords::basic_coords::e_slice_edge_sep::ESliceEdgeSepCoord;
+use crate::coords::phase_coords::dr::DRUDCoord;
+use crate::tables::registry::TableRegistry;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+use crate::rawcube::RawState;
+use crate::moveset::MoveSet;
+use crate::phases::step::Step;
+use crate::solver::{iter_solutions, SolutionIter};
+use crate::turndef::Turn;
+
+/// Finds domino reductions on the UD axis for a cube with edges oriented on the FB axis.
+/// Since the edges stay oriented, the pruning table holds the exact distance to DR.
+pub struct DRSolver {
+    coord_type: DRUDCoord,
+    move_tables: Arc<MoveTables>,
+    pruning_table: Arc<PruningTable>,
+}
+
+impl DRSolver {
+    pub fn new() -> Result<Self> {
+        Self::new_with_turns(&DRUDCoord::new().get_allowed_turns())
+    }
+
+    /// Builds a solver that only uses the given turns, which must keep the edges oriented
+    pub fn new_with_turns(move_set: &[Turn]) -> Result<Self> {
+        let coord_type = DRUDCoord::new();
+        let move_tables = TableRegistry::global().get_move_tables("DRUDCoord", move_set, coord_type.get_size(), |options| {
+            let co_move_tables = MoveTables::new_basic_table_with_options(COUDCoord::new(), move_set, options)?;
+            let edge_sep_move_tables = MoveTables::new_basic_table_with_options(ESliceEdgeSepCoord::new(), move_set, options)?;
+            MoveTables::new_product_table(&co_move_tables, &edge_sep_move_tables)
+        })?;
+
+        // DR is at most 12 turns away, so two distances fit in each byte
+        let pruning_table = TableRegistry::global().get_pruning_table(coord_type, &move_tables, PruningEncoding::Nibble)?;
+        Ok(Self {
+            coord_type,
+            move_tables,
+            pruning_table,
+        })
+    }
+
+    /// Returns the number of turns needed to reach DR, which is only known once EO is solved
+    pub fn get_distance(&self, state: &RawState) -> Result<u8> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        self.pruning_table.get_distance(self.coord_type.convert_raw_state_to_coord(state))
+    }
+
+    /// Returns every DR of at most slack turns more than optimal using <U, D, R, L, F2, B2>, shortest first
+    pub fn solve(&self, state: &RawState, slack: u8) -> Result<SolutionIter<'_>> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        let coord = self.coord_type.convert_raw_state_to_coord(state);
+        iter_solutions(coord, &self.move_tables, &self.pruning_table, slack)
+    }
+}
+
+impl Step for DRSolver {
+    fn get_name(&self) -> &str {
+        "DR"
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        MoveSet::new(self.move_tables.get_turns())
+    }
+
+    fn can_start(&self, state: &RawState) -> bool {
+        EOFBCoord::new().convert_raw_state_to_coord(state) == 0
+    }
+
+    fn get_heuristic(&self, state: &RawState) -> Result<u8> {
+        self.get_distance(state)
+    }
+
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>> {
+        Ok(Box::new(self.solve(state, slack)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::phase_coords::dr::is_in_dr;
+
+    #[test]
+    fn test_solve() {
+        let solver = DRSolver::new().unwrap();
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("R U2 F2 L' D R2 B2 U'"));
+        let distance = solver.get_distance(&scramble).unwrap();
+        assert!(distance > 0);
+
+        let solutions: Vec<Vec<Turn>> = solver.solve(&scramble, 0).unwrap().collect();
+        assert!(!solutions.is_empty());
+        for solution in solutions {
+            assert_eq!(solution.len(), distance as usize);
+            let mut state = scramble.clone();
+            state.apply_algorithm(&solution);
+            assert!(is_in_dr(&state));
+        }
+    }
+
+    #[test]
+    fn test_solve_requires_eo() {
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("F"));
+        assert!(matches!(DRSolver::new().unwrap().solve(&scramble, 0), Err(Error::CannotStartStep(step)) if step == "DR"));
+        assert!(matches!(DRSolver::new().unwrap().get_distance(&scramble), Err(Error::CannotStartStep(step)) if step == "DR"));
+    }
+}
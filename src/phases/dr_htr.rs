@@ -0,0 +1,160 @@
+use crate::error::{Error, Result};
+use crate::phases::eo::EOSolver;
+use crate::phases::dr::DRSolver;
+use crate::phases::htr::HTRSolver;
+use crate::phases::finish::FinishSolver;
+use crate::phases::method::{Method, StepLimits};
+use crate::phases::solution::MultiStepSolution;
+use crate::rotations::{get_eo_dr_orientations, rotate_algorithm, rotate_turn};
+use crate::turndef::{Turn, Algorithm};
+
+/// How many solutions of each step to try. Each step keeps solutions up to its
+/// slack above optimal, and at most the given number of the shortest ones. With niss, EO, DR
+/// and HTR are each also searched for on the inverse scramble. The finish is always on the normal scramble.
+#[derive(Clone, Copy, Debug)]
+pub struct DRHTRLimits {
+    pub eo_slack: u8,
+    pub max_eos: usize,
+    pub dr_slack: u8,
+    pub max_drs: usize,
+    pub htr_slack: u8,
+    pub max_htrs: usize,
+    pub niss: bool,
+}
+
+impl Default for DRHTRLimits {
+    fn default() -> Self {
+        Self {
+            eo_slack: 1,
+            max_eos: 4,
+            dr_slack: 1,
+            max_drs: 4,
+            htr_slack: 1,
+            max_htrs: 4,
+            niss: false,
+        }
+    }
+}
+
+/// Solves a cube using EO, then DR, then HTR, then finishing with half turns
+pub struct DRHTRSolver {
+    method: Method,
+}
+
+impl DRHTRSolver {
+    pub fn new(limits: &DRHTRLimits) -> Result<Self> {
+        let mut method = Method::new();
+        method.push_step(Box::new(EOSolver::new()?),
+            StepLimits { slack: limits.eo_slack, max_solutions: limits.max_eos, niss: limits.niss, ..StepLimits::default() });
+        method.push_step(Box::new(DRSolver::new()?),
+            StepLimits { slack: limits.dr_slack, max_solutions: limits.max_drs, niss: limits.niss, ..StepLimits::default() });
+        method.push_step(Box::new(HTRSolver::new()?),
+            StepLimits { slack: limits.htr_slack, max_solutions: limits.max_htrs, niss: limits.niss, ..StepLimits::default() });
+        method.push_step(Box::new(FinishSolver::new()?),
+            StepLimits { slack: 0, max_solutions: usize::MAX, niss: false, ..StepLimits::default() });
+        Ok(Self {
+            method,
+        })
+    }
+
+    /// Tries every EO axis and DR axis, and returns the shortest solution found after cancellations
+    pub fn solve(&self, scramble: &[Turn]) -> Result<MultiStepSolution> {
+        solve_in_orientations(&self.method, scramble, &get_eo_dr_orientations())?.ok_or(Error::NoSolution)
+    }
+}
+
+/// Solves a scramble with a method whose steps orient edges on the FB axis and reduce to DR on the UD axis.
+/// Other axes are found by rotating the scramble by each of the rotations, and the shortest solution
+/// is returned in the orientation of the scramble.
+pub fn solve_in_orientations(method: &Method, scramble: &[Turn], rotations: &[Vec<Turn>]) -> Result<Option<MultiStepSolution>> {
+    let mut best: Option<MultiStepSolution> = None;
+    for rotation in rotations {
+        let rotated_scramble = rotate_algorithm(scramble, rotation);
+        if let Some(solution) = method.solve(&rotated_scramble)? {
+            if best.as_ref().is_none_or(|best| solution.len() < best.len()) {
+                best = Some(unrotate_solution(&solution, rotation));
+            }
+        }
+    }
+    Ok(best)
+}
+
+/// Returns the names of the axes, in the orientation of the scramble, that a rotation
+/// brings to the FB axis for EO and the UD axis for DR
+pub fn get_orientation_axes(rotation: &[Turn]) -> (&'static str, &'static str) {
+    let inverse_rotation = rotation.invert();
+    let eo_axis = get_axis_name(&rotate_turn(&Turn::FRONT, &inverse_rotation));
+    let dr_axis = get_axis_name(&rotate_turn(&Turn::UP, &inverse_rotation));
+    (eo_axis, dr_axis)
+}
+
+// Rotates each step back to the orientation of the scramble, and names the axes used for EO and DR
+fn unrotate_solution(solution: &MultiStepSolution, rotation: &[Turn]) -> MultiStepSolution {
+    let inverse_rotation = rotation.invert();
+    let (eo_axis, dr_axis) = get_orientation_axes(rotation);
+
+    let mut unrotated = MultiStepSolution::new();
+    for step in &solution.steps {
+        let name = match step.name.as_str() {
+            "EO" => format!("EO-{}", eo_axis),
+            "DR" => format!("DR-{}", dr_axis),
+            name => name.to_string(),
+        };
+        unrotated.push_step_on_side(&name, rotate_algorithm(&step.turns, &inverse_rotation), step.side);
+    }
+    unrotated
+}
+
+fn get_axis_name(turn: &Turn) -> &'static str {
+    if turn.is_same_axis(&Turn::RIGHT) {
+        "lr"
+    } else if turn.is_same_axis(&Turn::UP) {
+        "ud"
+    } else {
+        "fb"
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rawcube::RawState;
+
+    #[test]
+    fn test_solve_dr_htr() {
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let limits = DRHTRLimits {
+            max_eos: 1,
+            max_drs: 1,
+            max_htrs: 1,
+            ..DRHTRLimits::default()
+        };
+        let solution = DRHTRSolver::new(&limits).unwrap().solve(&scramble).unwrap();
+        assert_eq!(solution.steps.len(), 4);
+
+        let mut state = RawState::solved();
+        state.apply_algorithm(&scramble);
+        state.apply_algorithm(&solution.get_turns());
+        assert_eq!(state, RawState::solved());
+    }
+
+    #[test]
+    fn test_solve_with_niss() {
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let limits = DRHTRLimits {
+            max_eos: 1,
+            max_drs: 1,
+            max_htrs: 1,
+            ..DRHTRLimits::default()
+        };
+        let linear = DRHTRSolver::new(&limits).unwrap().solve(&scramble).unwrap();
+        let solution = DRHTRSolver::new(&DRHTRLimits { niss: true, ..limits }).unwrap().solve(&scramble).unwrap();
+        assert!(solution.len() <= linear.len());
+
+        let mut state = RawState::solved();
+        state.apply_algorithm(&scramble);
+        state.apply_algorithm(&solution.get_turns());
+        assert_eq!(state, RawState::solved());
+    }
+}
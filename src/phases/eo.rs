@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::Result;
+use crate::coords::basic_coords::eo_fb::EOFBCoord;
+use crate::tables::registry::TableRegistry;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+use crate::rawcube::RawState;
+use crate::moveset::MoveSet;
+use crate::phases::step::Step;
+use crate::solver::{iter_solutions, SolutionIter};
+use crate::turndef::Turn;
+
+/// Finds ways to orient the edges on the FB axis using any outer layer turns
+pub struct EOSolver {
+    coord_type: EOFBCoord,
+    move_tables: Arc<MoveTables>,
+    pruning_table: Arc<PruningTable>,
+}
+
+impl EOSolver {
+    pub fn new() -> Result<Self> {
+        Self::new_with_turns(&EOFBCoord::new().get_allowed_turns())
+    }
+
+    /// Builds a solver that only uses the given turns
+    pub fn new_with_turns(move_set: &[Turn]) -> Result<Self> {
+        let coord_type = EOFBCoord::new();
+        let move_tables = TableRegistry::global().get_basic_move_tables(coord_type, move_set)?;
+        let pruning_table = TableRegistry::global().get_pruning_table(coord_type, &move_tables, PruningEncoding::Byte)?;
+        Ok(Self {
+            coord_type,
+            move_tables,
+            pruning_table,
+        })
+    }
+
+    pub fn get_distance(&self, state: &RawState) -> Result<u8> {
+        self.pruning_table.get_distance(self.coord_type.convert_raw_state_to_coord(state))
+    }
+
+    /// Returns every EO of at most slack turns more than optimal, shortest first
+    pub fn solve(&self, state: &RawState, slack: u8) -> Result<SolutionIter<'_>> {
+        let coord = self.coord_type.convert_raw_state_to_coord(state);
+        iter_solutions(coord, &self.move_tables, &self.pruning_table, slack)
+    }
+}
+
+impl Step for EOSolver {
+    fn get_name(&self) -> &str {
+        "EO"
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        MoveSet::new(self.move_tables.get_turns())
+    }
+
+    fn can_start(&self, _state: &RawState) -> bool {
+        true
+    }
+
+    fn get_heuristic(&self, state: &RawState) -> Result<u8> {
+        self.get_distance(state)
+    }
+
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>> {
+        Ok(Box::new(self.solve(state, slack)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let solver = EOSolver::new().unwrap();
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("F R B' U2 L"));
+        let distance = solver.get_distance(&scramble).unwrap();
+        assert!(distance > 0);
+
+        for solution in solver.solve(&scramble, 1).unwrap() {
+            assert!(solution.len() <= distance as usize + 1);
+            let mut state = scramble.clone();
+            state.apply_algorithm(&solution);
+            assert_eq!(solver.get_distance(&state).unwrap(), 0);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::{Error, Result};
+use crate::coords::phase_coords::half_turn::{HalfTurnCoord, HalfTurnCornerCoord, HalfTurnEdgeCoord, is_in_half_turn_group};
+use crate::tables::registry::TableRegistry;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+use crate::rawcube::RawState;
+use crate::moveset::MoveSet;
+use crate::phases::step::Step;
+use crate::solver::{iter_solutions, SolutionIter};
+use crate::turndef::Turn;
+
+/// Solves a cube in half turn reduction using only <U2, D2, R2, L2, F2, B2>.
+/// The group is small enough that the pruning table holds the exact distance of every position.
+pub struct FinishSolver {
+    coord_type: HalfTurnCoord,
+    move_tables: Arc<MoveTables>,
+    distance_table: Arc<PruningTable>,
+}
+
+impl FinishSolver {
+    pub fn new() -> Result<Self> {
+        Self::new_with_turns(&HalfTurnCoord::new().get_allowed_turns())
+    }
+
+    /// Builds a solver that only uses the given turns, which must be half turns
+    pub fn new_with_turns(half_turns: &[Turn]) -> Result<Self> {
+        let coord_type = HalfTurnCoord::new();
+
+        // Corners and edges move independently, so their small tables are combined
+        // rather than generating the full table from raw states.
+        let move_tables = TableRegistry::global().get_move_tables("HalfTurnCoord", half_turns, coord_type.get_size(), |options| {
+            let corner_move_tables = MoveTables::new_subgroup_table_with_options(HalfTurnCornerCoord::new(), half_turns, options)?;
+            let edge_move_tables = MoveTables::new_subgroup_table_with_options(HalfTurnEdgeCoord::new(), half_turns, options)?;
+            MoveTables::new_product_table(&corner_move_tables, &edge_move_tables)
+        })?;
+
+        let distance_table = TableRegistry::global().get_pruning_table(coord_type, &move_tables, PruningEncoding::Byte)?;
+        Ok(Self {
+            coord_type,
+            move_tables,
+            distance_table,
+        })
+    }
+
+    /// Returns the exact number of half turns needed to solve a state, which must be in half turn reduction
+    pub fn get_distance(&self, state: &RawState) -> Result<u8> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        self.distance_table.get_distance(self.coord_type.convert_raw_state_to_coord(state))
+    }
+
+    /// Returns every finish that is at most slack turns longer than optimal, shortest first
+    pub fn solve(&self, state: &RawState, slack: u8) -> Result<SolutionIter<'_>> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        let coord = self.coord_type.convert_raw_state_to_coord(state);
+        iter_solutions(coord, &self.move_tables, &self.distance_table, slack)
+    }
+}
+
+impl Step for FinishSolver {
+    fn get_name(&self) -> &str {
+        "Finish"
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        MoveSet::new(self.move_tables.get_turns())
+    }
+
+    fn can_start(&self, state: &RawState) -> bool {
+        is_in_half_turn_group(state)
+    }
+
+    fn get_heuristic(&self, state: &RawState) -> Result<u8> {
+        self.get_distance(state)
+    }
+
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>> {
+        Ok(Box::new(self.solve(state, slack)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_state(alg: &str) -> RawState {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string(alg));
+        state
+    }
+
+    #[test]
+    fn test_solve_optimally() {
+        let solver = FinishSolver::new().unwrap();
+        assert_eq!(solver.get_distance(&RawState::solved()).unwrap(), 0);
+        assert_eq!(solver.get_distance(&get_state("R2")).unwrap(), 1);
+
+        let scramble = get_state("R2 U2 F2 R2 D2 L2 B2 U2");
+        let distance = solver.get_distance(&scramble).unwrap();
+        let solutions: Vec<Vec<Turn>> = solver.solve(&scramble, 0).unwrap().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert_eq!(solution.len(), distance as usize);
+            let mut state = scramble.clone();
+            state.apply_algorithm(solution);
+            assert_eq!(solver.get_distance(&state).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_solve_with_slack() {
+        let solver = FinishSolver::new().unwrap();
+        let scramble = get_state("R2 F2 U2");
+        let solutions: Vec<Vec<Turn>> = solver.solve(&scramble, 4).unwrap().collect();
+        assert_eq!(solutions[0].len(), 3);
+        assert!(solutions.iter().any(|solution| solution.len() > 3));
+        for solution in &solutions {
+            for pair in solution.windows(2) {
+                assert!(pair[1].can_follow(&pair[0]));
+            }
+        }
+        assert!(solutions.iter().all(|solution| solution.len() <= 7));
+    }
+
+    #[test]
+    fn test_requires_half_turn_reduction() {
+        let solver = FinishSolver::new().unwrap();
+        assert!(matches!(solver.solve(&get_state("R"), 0), Err(Error::CannotStartStep(step)) if step == "Finish"));
+        assert_eq!(solver.get_distance(&get_state("R2 U")), Err(Error::CannotStartStep("Finish".to_string())));
+    }
+}
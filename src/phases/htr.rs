@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::{Error, Result};
+use crate::coords::phase_coords::htr::HTRCoord;
+use crate::coords::phase_coords::dr::is_in_dr;
+use crate::tables::registry::TableRegistry;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+use crate::rawcube::RawState;
+use crate::moveset::MoveSet;
+use crate::phases::step::Step;
+use crate::solver::{iter_solutions, SolutionIter};
+use crate::turndef::Turn;
+
+/// Finds half turn reductions for a cube in domino reduction on the UD axis
+pub struct HTRSolver {
+    coord_type: HTRCoord,
+    move_tables: Arc<MoveTables>,
+    pruning_table: Arc<PruningTable>,
+}
+
+impl HTRSolver {
+    pub fn new() -> Result<Self> {
+        Self::new_with_turns(&HTRCoord::new().get_allowed_turns())
+    }
+
+    /// Builds a solver that only uses the given turns, which must stay within DR
+    pub fn new_with_turns(move_set: &[Turn]) -> Result<Self> {
+        let coord_type = HTRCoord::new();
+        let move_tables = TableRegistry::global().get_move_tables("HTRCoord", move_set, coord_type.get_size(),
+            |options| MoveTables::new_subgroup_table_with_options(coord_type, move_set, options))?;
+        let pruning_table = TableRegistry::global().get_pruning_table(coord_type, &move_tables, PruningEncoding::Byte)?;
+        Ok(Self {
+            coord_type,
+            move_tables,
+            pruning_table,
+        })
+    }
+
+    /// Returns the number of turns needed to reach HTR, which is only known once DR is solved
+    pub fn get_distance(&self, state: &RawState) -> Result<u8> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        self.pruning_table.get_distance(self.coord_type.convert_raw_state_to_coord(state))
+    }
+
+    /// Returns every HTR of at most slack turns more than optimal using <U, D, R2, L2, F2, B2>, shortest first
+    pub fn solve(&self, state: &RawState, slack: u8) -> Result<SolutionIter<'_>> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.get_name().to_string()));
+        }
+        let coord = self.coord_type.convert_raw_state_to_coord(state);
+        iter_solutions(coord, &self.move_tables, &self.pruning_table, slack)
+    }
+}
+
+impl Step for HTRSolver {
+    fn get_name(&self) -> &str {
+        "HTR"
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        MoveSet::new(self.move_tables.get_turns())
+    }
+
+    fn can_start(&self, state: &RawState) -> bool {
+        is_in_dr(state)
+    }
+
+    fn get_heuristic(&self, state: &RawState) -> Result<u8> {
+        self.get_distance(state)
+    }
+
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>> {
+        Ok(Box::new(self.solve(state, slack)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Algorithm;
+
+    fn get_state(alg: &str) -> RawState {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string(alg));
+        state
+    }
+
+    #[test]
+    fn test_pruning_table_covers_dr() {
+        // Every coordinate is reachable within domino reduction, otherwise table generation would fail
+        let solver = HTRSolver::new().unwrap();
+        assert_eq!(solver.get_distance(&RawState::solved()).unwrap(), 0);
+        assert_eq!(solver.get_distance(&get_state("U")).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = HTRSolver::new().unwrap();
+        let scramble = get_state("R2 U F2 D' L2 U");
+        let optimal_length = solver.get_distance(&scramble).unwrap();
+        assert!(optimal_length > 0 && optimal_length <= 6);
+
+        let solutions: Vec<Vec<Turn>> = solver.solve(&scramble, 1).unwrap().collect();
+        assert_eq!(solutions[0].len(), optimal_length as usize);
+        for solution in &solutions {
+            let mut state = scramble.clone();
+            state.apply_algorithm(solution);
+            assert_eq!(solver.get_distance(&state).unwrap(), 0, "{} is not an HTR", solution.to_algorithm_string());
+        }
+    }
+
+    #[test]
+    fn test_solve_requires_dr() {
+        assert!(matches!(HTRSolver::new().unwrap().solve(&get_state("R"), 1), Err(Error::CannotStartStep(step)) if step == "HTR"));
+        assert!(matches!(HTRSolver::new().unwrap().get_distance(&get_state("R")), Err(Error::CannotStartStep(step)) if step == "HTR"));
+    }
+}
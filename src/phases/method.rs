@@ -0,0 +1,116 @@
+use crate::error::Result;
+use crate::phases::solution::{MultiStepSolution, Side};
+use crate::phases::step::Step;
+use crate::turndef::Turn;
+
+/// How many solutions of a step to try. The step keeps solutions up to slack turns above optimal and
+/// at most max_length turns long, and at most max_solutions of the shortest ones. With niss, the step
+/// is also searched for on the inverse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepLimits {
+    pub slack: u8,
+    pub max_length: u8,
+    pub max_solutions: usize,
+    pub niss: bool,
+}
+
+impl Default for StepLimits {
+    fn default() -> Self {
+        Self {
+            slack: 1,
+            max_length: u8::MAX,
+            max_solutions: 4,
+            niss: false,
+        }
+    }
+}
+
+/// A method solves a scramble by solving each of its steps in order. Every combination of the
+/// solutions allowed by each step's limits is tried, and the shortest full solution is kept.
+pub struct Method {
+    steps: Vec<(Box<dyn Step>, StepLimits)>,
+}
+
+impl Method {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push_step(&mut self, step: Box<dyn Step>, limits: StepLimits) {
+        self.steps.push((step, limits));
+    }
+
+    pub fn get_steps(&self) -> impl Iterator<Item = &dyn Step> {
+        self.steps.iter().map(|(step, _)| step.as_ref())
+    }
+
+    /// Returns the shortest solution found after cancellations, or nothing if some
+    /// step could not be started from any solution of the steps before it.
+    pub fn solve(&self, scramble: &[Turn]) -> Result<Option<MultiStepSolution>> {
+        let mut best = None;
+        self.solve_from_step(0, scramble, &MultiStepSolution::new(), &mut best)?;
+        Ok(best)
+    }
+
+    fn solve_from_step(&self, step_index: usize, scramble: &[Turn], solution: &MultiStepSolution, best: &mut Option<MultiStepSolution>) -> Result<()> {
+        let Some((step, limits)) = self.steps.get(step_index) else {
+            if best.as_ref().is_none_or(|best| solution.len() < best.len()) {
+                *best = Some(solution.clone());
+            }
+            return Ok(());
+        };
+
+        let sides = if limits.niss { vec![Side::Normal, Side::Inverse] } else { vec![Side::Normal] };
+        for side in sides {
+            let state = solution.get_state(scramble, side);
+            if !step.can_start(&state) {
+                continue;
+            }
+            let solutions = step.solve_step(&state, limits.slack)?
+                .take_while(|turns| turns.len() <= limits.max_length as usize)
+                .take(limits.max_solutions);
+            for turns in solutions {
+                let mut next_solution = solution.clone();
+                next_solution.push_step_on_side(step.get_name(), turns, side);
+                self.solve_from_step(step_index + 1, scramble, &next_solution, best)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::BasicCoordinate;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::moveset::MoveSet;
+    use crate::phases::eo::EOSolver;
+    use crate::phases::step::{CoordinateStep, StepGoal};
+
+    #[test]
+    fn test_custom_method() {
+        // Orients edges, then orients corners without breaking the edge orientation
+        let co = COUDCoord::new();
+        let co_step = CoordinateStep::new_with_requirement("CO", vec![StepGoal::new_basic(co, &MoveSet::eo_fb()).unwrap()],
+            |state| EOFBCoord::new().convert_raw_state_to_coord(state) == 0).unwrap();
+
+        let mut method = Method::new();
+        method.push_step(Box::new(EOSolver::new().unwrap()), StepLimits { niss: true, ..StepLimits::default() });
+        method.push_step(Box::new(co_step), StepLimits::default());
+        assert_eq!(method.get_steps().map(|step| step.get_name()).collect::<Vec<&str>>(), vec!["EO", "CO"]);
+
+        let scramble = Turn::get_vec_from_alg_string("R' U' F D2 L2 F R2 U2 R2 B D2 L F' U R' F2 D' B U' R' U' F");
+        let solution = method.solve(&scramble).unwrap().unwrap();
+        assert_eq!(solution.steps.len(), 2);
+
+        // With premoves from the inverse, only the scramble with premoves is solved until the whole cube is
+        let state = solution.get_state(&scramble, Side::Normal);
+        assert_eq!(EOFBCoord::new().convert_raw_state_to_coord(&state), 0);
+        assert_eq!(co.convert_raw_state_to_coord(&state), 0);
+    }
+}
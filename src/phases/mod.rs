@@ -0,0 +1,9 @@
+pub mod eo;
+pub mod dr;
+pub mod htr;
+pub mod finish;
+pub mod solution;
+pub mod step;
+pub mod method;
+pub mod dr_htr;
+pub mod config;
@@ -0,0 +1,141 @@
+use crate::rawcube::RawState;
+use crate::turndef::{Turn, Algorithm};
+
+/// Which scramble a step was found on. Steps found on the inverse scramble are applied
+/// to the normal scramble as premoves, by inverting them and placing them at the start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Normal,
+    Inverse,
+}
+
+/// A named part of a solution, such as EO or DR
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolutionStep {
+    pub name: String,
+    pub turns: Vec<Turn>,
+    pub side: Side,
+}
+
+/// A solution made up of consecutive steps, each found on the normal or the inverse scramble.
+/// The linear solution is the normal turns followed by the inverse of the inverse turns. Turns at the
+/// end of one step may cancel with turns at the start of the next, so the length is counted after cancellation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiStepSolution {
+    pub steps: Vec<SolutionStep>,
+}
+
+impl MultiStepSolution {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push_step(&mut self, name: &str, turns: Vec<Turn>) {
+        self.push_step_on_side(name, turns, Side::Normal);
+    }
+
+    pub fn push_step_on_side(&mut self, name: &str, turns: Vec<Turn>, side: Side) {
+        self.steps.push(SolutionStep {
+            name: name.to_string(),
+            turns,
+            side,
+        });
+    }
+
+    /// Returns the turns of every step found on the given side, in order
+    pub fn get_turns_on_side(&self, side: Side) -> Vec<Turn> {
+        get_turns_on_side(&self.steps, side)
+    }
+
+    /// Returns the linear solution, with the inverse turns inverted and moved to the end
+    pub fn get_turns(&self) -> Vec<Turn> {
+        get_linear_turns(&self.steps)
+    }
+
+    /// Returns the state seen by the next step on the given side. On the normal side this is the scramble with
+    /// the inverse turns so far as premoves, followed by the normal turns so far. The inverse side sees its inverse.
+    pub fn get_state(&self, scramble: &[Turn], side: Side) -> RawState {
+        let mut state = RawState::solved();
+        state.apply_algorithm(&self.get_turns_on_side(Side::Inverse).invert());
+        state.apply_algorithm(scramble);
+        state.apply_algorithm(&self.get_turns_on_side(Side::Normal));
+        match side {
+            Side::Normal => state,
+            Side::Inverse => state.invert(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.get_turns().len()
+    }
+
+    /// Writes one line per step, with the length of the step and the total length so far, followed by the full solution.
+    /// Steps on the inverse are written in brackets. For example: "(R' U2 F) // EO-fb (3/3)"
+    pub fn to_annotated_string(&self) -> String {
+        let mut lines = Vec::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            let alg = match step.side {
+                Side::Normal => step.turns.to_algorithm_string(),
+                Side::Inverse => format!("({})", step.turns.to_algorithm_string()),
+            };
+            lines.push(format!("{} // {} ({}/{})",
+                alg, step.name, step.turns.len(), get_linear_turns(&self.steps[..=i]).len()));
+        }
+        lines.push(format!("Solution ({}): {}", self.len(), self.get_turns().to_algorithm_string()));
+        lines.join("\n")
+    }
+}
+
+fn get_turns_on_side(steps: &[SolutionStep], side: Side) -> Vec<Turn> {
+    let mut turns = Vec::new();
+    for step in steps.iter().filter(|step| step.side == side) {
+        turns.extend(step.turns.iter());
+    }
+    turns
+}
+
+fn get_linear_turns(steps: &[SolutionStep]) -> Vec<Turn> {
+    let mut turns = get_turns_on_side(steps, Side::Normal);
+    turns.extend(get_turns_on_side(steps, Side::Inverse).invert());
+    turns.cancel_turns()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotated_string() {
+        let mut solution = MultiStepSolution::new();
+        solution.push_step("EO", Turn::get_vec_from_alg_string("F R U"));
+        solution.push_step("DR", Turn::get_vec_from_alg_string("U D2 R"));
+        assert_eq!(solution.len(), 5);
+        assert_eq!(solution.to_annotated_string(),
+            "F R U // EO (3/3)\nU D2 R // DR (3/5)\nSolution (5): F R U2 D2 R");
+    }
+
+    #[test]
+    fn test_inverse_steps() {
+        let scramble = Turn::get_vec_from_alg_string("R U F");
+        let mut solution = MultiStepSolution::new();
+        solution.push_step_on_side("EO", Turn::get_vec_from_alg_string("R'"), Side::Inverse);
+        assert_eq!(solution.get_turns(), Turn::get_vec_from_alg_string("R"));
+
+        // The premove R makes the normal scramble R R U F, which F' U' R2 solves, cancelling into F' U' R'
+        let mut expected = RawState::solved();
+        expected.apply_algorithm(&Turn::get_vec_from_alg_string("R R U F"));
+        assert_eq!(solution.get_state(&scramble, Side::Normal), expected);
+        assert_eq!(solution.get_state(&scramble, Side::Inverse), expected.invert());
+
+        solution.push_step("Finish", Turn::get_vec_from_alg_string("F' U' R2"));
+        let mut state = RawState::solved();
+        state.apply_algorithm(&scramble);
+        state.apply_algorithm(&solution.get_turns());
+        assert_eq!(state, RawState::solved());
+        assert_eq!(solution.to_annotated_string(),
+            "(R') // EO (1/1)\nF' U' R2 // Finish (3/3)\nSolution (3): F' U' R'");
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::coords::BasicCoordinate;
+use crate::error::{Error, Result};
+use crate::moveset::MoveSet;
+use crate::tables::registry::TableRegistry;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding, GoalSet};
+use crate::rawcube::RawState;
+use crate::solver::{SearchTables, SolutionIter};
+use crate::turndef::Turn;
+
+/*
+A step is one part of a method, such as EO or DR. It has a goal made up of one or more coordinates that must
+all be solved, a set of turns it may use to get there, and a heuristic giving a lower bound on the number of
+turns needed, which is 0 exactly when the goal is reached.
+*/
+pub trait Step {
+    fn get_name(&self) -> &str;
+    fn get_allowed_turns(&self) -> MoveSet;
+
+    /// Whether the step can be started from this state, such as DR needing edges to be oriented first
+    fn can_start(&self, state: &RawState) -> bool;
+    fn get_heuristic(&self, state: &RawState) -> Result<u8>;
+
+    /// Returns every solution of at most slack turns more than optimal, shortest first.
+    /// States the step can not start from give an error.
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>>;
+
+    fn is_solved(&self, state: &RawState) -> Result<bool> {
+        Ok(self.get_heuristic(state)? == 0)
+    }
+}
+
+/// One coordinate of a step's goal, with the tables used to search for it
+pub struct StepGoal {
+    to_coord: Box<dyn Fn(&RawState) -> usize>,
+    move_tables: Arc<MoveTables>,
+    pruning_table: Arc<PruningTable>,
+}
+
+impl StepGoal {
+    pub fn new(to_coord: Box<dyn Fn(&RawState) -> usize>, move_tables: Arc<MoveTables>, pruning_table: Arc<PruningTable>) -> Self {
+        Self {
+            to_coord,
+            move_tables,
+            pruning_table,
+        }
+    }
+
+    /// Gets the shared move and pruning tables for a basic coordinate over the given turns
+    pub fn new_basic<C: BasicCoordinate + 'static>(coord_type: C, move_set: &[Turn]) -> Result<Self> {
+        let move_tables = TableRegistry::global().get_basic_move_tables(coord_type, move_set)?;
+        let pruning_table = TableRegistry::global().get_pruning_table(coord_type, &move_tables, PruningEncoding::Byte)?;
+        Ok(Self::new(Box::new(move |state| coord_type.convert_raw_state_to_coord(state)), move_tables, pruning_table))
+    }
+
+    /// Like new_basic, but with a pruning table towards the goal set rather than the solved coordinates.
+    /// The goal name tells the table apart from other goal sets of the coordinate in the disk cache.
+    pub fn new_basic_with_goals<C: BasicCoordinate + 'static>(coord_type: C, move_set: &[Turn], goal_name: &str, goals: &GoalSet) -> Result<Self> {
+        let move_tables = TableRegistry::global().get_basic_move_tables(coord_type, move_set)?;
+        let pruning_table = TableRegistry::global().get_pruning_table_with_goals(coord_type, &move_tables, PruningEncoding::Byte, goal_name, goals)?;
+        Ok(Self::new(Box::new(move |state| coord_type.convert_raw_state_to_coord(state)), move_tables, pruning_table))
+    }
+
+    fn get_coord(&self, state: &RawState) -> usize {
+        (self.to_coord)(state)
+    }
+}
+
+/// A step defined entirely by its goal coordinates, for defining new steps without new solver code.
+/// The turns of the first goal's move tables are the turns the step may use, and every other goal's
+/// move tables must include them.
+pub struct CoordinateStep {
+    name: String,
+    goals: Vec<StepGoal>,
+    requirement: fn(&RawState) -> bool,
+}
+
+impl CoordinateStep {
+    pub fn new(name: &str, goals: Vec<StepGoal>) -> Result<Self> {
+        Self::new_with_requirement(name, goals, |_| true)
+    }
+
+    /// Defines a step that can only be started from states meeting the requirement. There must be at least one goal.
+    pub fn new_with_requirement(name: &str, goals: Vec<StepGoal>, requirement: fn(&RawState) -> bool) -> Result<Self> {
+        if goals.is_empty() {
+            return Err(Error::NoStepGoals);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            goals,
+            requirement,
+        })
+    }
+
+    fn get_coords(&self, state: &RawState) -> Vec<usize> {
+        self.goals.iter().map(|goal| goal.get_coord(state)).collect()
+    }
+
+    fn get_search_tables(&self) -> Vec<SearchTables<'_>> {
+        self.goals.iter().map(|goal| (&*goal.move_tables, &*goal.pruning_table)).collect()
+    }
+}
+
+impl Step for CoordinateStep {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_allowed_turns(&self) -> MoveSet {
+        MoveSet::new(self.goals[0].move_tables.get_turns())
+    }
+
+    fn can_start(&self, state: &RawState) -> bool {
+        (self.requirement)(state)
+    }
+
+    fn get_heuristic(&self, state: &RawState) -> Result<u8> {
+        let mut heuristic = 0;
+        for goal in &self.goals {
+            heuristic = heuristic.max(goal.pruning_table.get_exact_distance(goal.get_coord(state), &goal.move_tables)?);
+        }
+        Ok(heuristic)
+    }
+
+    fn solve_step(&self, state: &RawState, slack: u8) -> Result<Box<dyn Iterator<Item = Vec<Turn>> + '_>> {
+        if !self.can_start(state) {
+            return Err(Error::CannotStartStep(self.name.clone()));
+        }
+        Ok(Box::new(SolutionIter::new(&self.get_coords(state), &self.get_search_tables(), slack)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::Coordinate;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+
+    #[test]
+    fn test_coordinate_step() {
+        let eo = EOFBCoord::new();
+        let co = COUDCoord::new();
+        let turns = eo.get_allowed_turns();
+        let step = CoordinateStep::new("EO and CO", vec![StepGoal::new_basic(eo, &turns).unwrap(), StepGoal::new_basic(co, &turns).unwrap()]).unwrap();
+        let allowed_turns = step.get_allowed_turns();
+        assert_eq!(allowed_turns.len(), turns.len());
+        assert!(turns.is_subset_of(&allowed_turns));
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("F R U' B L2 D"));
+        assert!(step.can_start(&scramble));
+        assert!(!step.is_solved(&scramble).unwrap());
+
+        let heuristic = step.get_heuristic(&scramble).unwrap();
+        let solutions: Vec<Vec<Turn>> = step.solve_step(&scramble, 0).unwrap().collect();
+        assert!(!solutions.is_empty());
+        for solution in solutions {
+            assert!(solution.len() >= heuristic as usize);
+            let mut state = scramble.clone();
+            state.apply_algorithm(&solution);
+            assert!(step.is_solved(&state).unwrap());
+            assert_eq!(eo.convert_raw_state_to_coord(&state), 0);
+            assert_eq!(co.convert_raw_state_to_coord(&state), 0);
+        }
+    }
+
+    #[test]
+    fn test_step_needs_goals() {
+        assert!(matches!(CoordinateStep::new("Nothing", Vec::new()), Err(Error::NoStepGoals)));
+    }
+}
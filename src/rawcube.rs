@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use crate::turndef::Turn;
 
 /*
@@ -243,7 +244,7 @@ The state of the cube is represented by a set of lists of each of the above type
 The lists are ordered in the same order as the pieces are defined.
 */
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StateList<S: PieceState + Copy> {
     states: Vec<S>,
 }
@@ -317,7 +318,7 @@ impl StateList<Twist> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RawState {
     pub corners: StateList<Corner>,
     pub twists: StateList<Twist>,
@@ -336,6 +337,37 @@ impl RawState {
             centers: StateList { states: vec![Centre::U, Centre::L, Centre::F, Centre::R, Centre::B, Centre::D] },
         }
     }
+
+    pub fn apply_turn(&mut self, turn: &Turn) {
+        for base_turn in turn.to_base_turns() {
+            TurnEffect::from_turn(&base_turn).expect("Turns are made up of base turns").apply(self);
+        }
+    }
+
+    pub fn apply_algorithm(&mut self, turns: &[Turn]) {
+        for turn in turns {
+            self.apply_turn(turn);
+        }
+    }
+
+    /// Returns the state reached by applying the inverse of the algorithm that reached this state.
+    /// Each piece goes to the position of the piece that is in its home position, with the opposite orientation.
+    pub fn invert(&self) -> RawState {
+        let mut inverse = self.clone();
+        for (position, corner) in self.corners.as_slice().iter().enumerate() {
+            let twist = *self.twists.get(&position) as usize;
+            inverse.corners.set(corner, &position.into());
+            inverse.twists.set(corner, &(3 - twist).into());
+        }
+        for (position, edge) in self.edges.as_slice().iter().enumerate() {
+            inverse.edges.set(edge, &position.into());
+            inverse.flips.set(edge, self.flips.get(&position));
+        }
+        for (position, centre) in self.centers.as_slice().iter().enumerate() {
+            inverse.centers.set(centre, &position.into());
+        }
+        inverse
+    }
 }
 
 /*
@@ -478,12 +510,12 @@ const BACK_LAYER_EFFECT: TurnEffect = TurnEffect {
     edge_swaps: &[ (Edge::UB, Edge::BL), (Edge::BL, Edge::DB), (Edge::DB, Edge::BR) ] ,
     center_swaps: &[] ,
     corner_twists: &[ (Corner::UBL, Twist::CW), (Corner::DBL, Twist::ACW), (Corner::DBR, Twist::CW), (Corner::UBR, Twist::ACW) ] ,
-    edge_flips: &[] ,
+    edge_flips: &[Edge::UB, Edge::BL, Edge::DB, Edge::BR] ,
 };
 
 impl TurnEffect {
-    pub fn from_turn(turn: &Turn) -> Self {
-        match turn {
+    pub fn from_turn(turn: &Turn) -> Result<Self> {
+        Ok(match turn {
             &Turn::RIGHT => RIGHT_LAYER_EFFECT,
             &Turn::MIDDLE => MIDDLE_LAYER_EFFECT,
             &Turn::LEFT => LEFT_LAYER_EFFECT,
@@ -493,7 +525,39 @@ impl TurnEffect {
             &Turn::FRONT => FRONT_LAYER_EFFECT,
             &Turn::SLICE => SLICE_LAYER_EFFECT,
             &Turn::BACK => BACK_LAYER_EFFECT,
-            _ => panic!("Raw move effects are only available for base moves of a single layer: {:?} ({:?}) is not supported. For compound moves, use move tables.", turn, turn.to_name()),
+            _ => return Err(Error::CompoundTurn(*turn)),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turndef::Algorithm;
+
+    #[test]
+    fn test_invert() {
+        let alg = Turn::get_vec_from_alg_string("R U' F2 L D B' M E2 S'");
+        let mut state = RawState::solved();
+        state.apply_algorithm(&alg);
+        let mut inverse = RawState::solved();
+        inverse.apply_algorithm(&alg.invert());
+
+        assert_eq!(state.invert(), inverse);
+        assert_eq!(state.invert().invert(), state);
+        assert_eq!(RawState::solved().invert(), RawState::solved());
+    }
+
+    #[test]
+    fn test_back_turn_flips() {
+        // Quarter turns of F and B flip the four edges they move, relative to the FB axis
+        let mut state = RawState::solved();
+        state.apply_turn(&Turn::from_name("B"));
+        for edge in [Edge::UB, Edge::BL, Edge::DB, Edge::BR] {
+            assert_eq!(state.flips.get(&edge), &Flip::Bad);
         }
+        state.apply_turn(&Turn::from_name("B'"));
+        assert_eq!(state, RawState::solved());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,100 @@
+use crate::rawcube::RawState;
+use crate::turndef::{Turn, Algorithm};
+
+/*
+Rotating the whole cube relabels its faces. Solving a scramble from a different orientation is the same
+as solving the rotated scramble, and rotating the solution back. The rotated version of a turn T for a
+rotation X is X' T X, which we find by applying it to a solved cube and matching the result against
+every outer layer turn.
+*/
+
+pub fn rotate_turn(turn: &Turn, rotation: &[Turn]) -> Turn {
+    let mut state = RawState::solved();
+    state.apply_algorithm(&rotation.invert());
+    state.apply_turn(turn);
+    state.apply_algorithm(rotation);
+
+    for candidate in Turn::get_outer_layer_turns() {
+        let mut candidate_state = RawState::solved();
+        candidate_state.apply_turn(&candidate);
+        if candidate_state == state {
+            return candidate;
+        }
+    }
+    panic!("Rotating {:?} does not give an outer layer turn", turn.to_name());
+}
+
+pub fn rotate_algorithm(turns: &[Turn], rotation: &[Turn]) -> Vec<Turn> {
+    turns.iter().map(|turn| rotate_turn(turn, rotation)).collect()
+}
+
+/// Rotations that bring each pair of axes to the FB and UD axes, so that a solver for
+/// EO on FB followed by DR on UD covers every choice of EO axis and DR axis.
+pub fn get_eo_dr_orientations() -> Vec<Vec<Turn>> {
+    ["", "z", "x", "x z", "y", "y z"].iter()
+        .map(|rotation| Turn::get_vec_from_alg_string(rotation))
+        .collect()
+}
+
+/// All 24 orientations of the cube: each face is brought to U, then turned to each of its four sides
+pub fn get_all_orientations() -> Vec<Vec<Turn>> {
+    let mut rotations = Vec::new();
+    for face in ["", "x", "x2", "x'", "z", "z'"] {
+        for side in ["", "y", "y2", "y'"] {
+            rotations.push(Turn::get_vec_from_alg_string(&format!("{} {}", face, side)));
+        }
+    }
+    rotations
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_turn() {
+        let rotation = Turn::get_vec_from_alg_string("y");
+        for turn in Turn::get_outer_layer_turns() {
+            let rotated = rotate_turn(&turn, &rotation);
+            assert_eq!(rotate_turn(&rotated, &rotation.invert()), turn);
+        }
+        assert_eq!(rotate_turn(&Turn::from_name("U"), &rotation), Turn::from_name("U"));
+        assert_ne!(rotate_turn(&Turn::from_name("R"), &rotation), Turn::from_name("R"));
+    }
+
+    #[test]
+    fn test_rotate_algorithm_solves_rotated_scramble() {
+        let scramble = Turn::get_vec_from_alg_string("R U F' L2 D B");
+        let rotation = Turn::get_vec_from_alg_string("x z");
+
+        let mut state = RawState::solved();
+        state.apply_algorithm(&rotate_algorithm(&scramble, &rotation));
+        state.apply_algorithm(&rotate_algorithm(&scramble.invert(), &rotation));
+        assert_eq!(state, RawState::solved());
+    }
+
+    #[test]
+    fn test_eo_dr_orientations_are_distinct() {
+        let mut axis_pairs = Vec::new();
+        for rotation in get_eo_dr_orientations() {
+            let eo_axis = rotate_turn(&Turn::FRONT, &rotation).get_axis_mask();
+            let dr_axis = rotate_turn(&Turn::UP, &rotation).get_axis_mask();
+            assert!(!axis_pairs.contains(&(eo_axis, dr_axis)));
+            axis_pairs.push((eo_axis, dr_axis));
+        }
+        assert_eq!(axis_pairs.len(), 6);
+    }
+
+    #[test]
+    fn test_all_orientations_are_distinct() {
+        let mut states: Vec<RawState> = Vec::new();
+        for rotation in get_all_orientations() {
+            let mut state = RawState::solved();
+            state.apply_algorithm(&rotation);
+            assert!(!states.contains(&state));
+            states.push(state);
+        }
+        assert_eq!(states.len(), 24);
+    }
+}
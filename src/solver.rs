@@ -1,23 +1,357 @@
-use crate::coords::Coordinate;
+use std::collections::VecDeque;
+
+use crate::error::{Error, Result};
+use crate::heuristic::{Heuristic, MaxHeuristic};
 use crate::tables::movetables::MoveTables;
 use crate::tables::pruningtables::PruningTable;
+use crate::tables::successortables::SuccessorTable;
 use crate::turndef::Turn;
 
-pub fn solve_optimally(mut coord: usize, movetables: MoveTables, pruningtables: PruningTable) -> Vec<Turn> {
-    let mut current_distance = pruningtables.get_distance(coord);
-    let mut solution = Vec::new();
-
-    while current_distance > 0 {
-        for turn in movetables.get_turns() {
-            let new_coord = movetables.apply_move_to_coord(coord, turn);
-            let new_distance = pruningtables.get_distance(new_coord);
-            if new_distance < current_distance {
-                current_distance = new_distance;
-                solution.push(*turn);
-                coord = new_coord;
-                break;
+/*
+Searches track one or more coordinates, each with its own move tables. A heuristic gives a lower bound on the
+number of turns needed from the coordinates, and a position is solved when the heuristic is 0. Without a
+heuristic of its own, a search uses the largest distance given by the pruning table of each coordinate, since
+every coordinate must be solved. Every coordinate's move tables must include the turns of the first
+coordinate's move tables, which are the turns used by the search. Only canonical sequences are searched, so
+solutions never turn a layer twice in a row, and turns on the same axis always appear in the same order.
+This also means solutions differing only by the order of commuting turns are found once. The distances the
+heuristic keeps are passed down the search, as pruning tables storing distances modulo 3 can only give a
+distance from the one before it.
+*/
+pub type SearchTables<'a> = (&'a MoveTables, &'a PruningTable);
+
+// The move tables of one coordinate, with the index in them of each turn used by the search
+struct IndexedTables<'a> {
+    move_tables: &'a MoveTables,
+    turn_indices: Vec<usize>,
+}
+
+// A position of a search, with the distances the heuristic keeps for it
+#[derive(Clone)]
+struct Position {
+    coords: Vec<usize>,
+    distances: Vec<u8>,
+}
+
+// What a search needs besides the position it is at
+struct SearchContext<'a, 'b> {
+    tables: &'b [IndexedTables<'a>],
+    heuristic: &'b dyn Heuristic,
+    successors: &'b SuccessorTable,
+}
+
+// Stops solution iterators for positions that can not be solved by the given tables
+const MAX_SOLUTION_LENGTH: u8 = 30;
+
+pub fn solve_optimally(coord: usize, movetables: MoveTables, pruningtables: PruningTable) -> Result<Vec<Turn>> {
+    let tables = [(&movetables, &pruningtables)];
+    let max_depth = pruningtables.get_exact_distance(coord, &movetables)?;
+    Ok(ida_star(&[coord], &tables, max_depth)?.into_iter().next().unwrap_or_default())
+}
+
+/// Iterative deepening A* search. Returns every solution of optimal length, or nothing
+/// if there is no solution of at most max_depth turns.
+pub fn ida_star(coords: &[usize], tables: &[SearchTables], max_depth: u8) -> Result<Vec<Vec<Turn>>> {
+    let move_tables: Vec<&MoveTables> = tables.iter().map(|(move_tables, _)| *move_tables).collect();
+    ida_star_with_heuristic(coords, &move_tables, &MaxHeuristic::from_tables(tables), max_depth)
+}
+
+/// Iterative deepening A* search guided by any heuristic for the coordinates
+pub fn ida_star_with_heuristic(coords: &[usize], move_tables: &[&MoveTables], heuristic: &dyn Heuristic, max_depth: u8) -> Result<Vec<Vec<Turn>>> {
+    let tables = get_indexed_tables(move_tables)?;
+    let successors = SuccessorTable::new(move_tables[0].get_turns());
+    let context = SearchContext {
+        tables: &tables,
+        heuristic,
+        successors: &successors,
+    };
+    let position = get_position(coords, heuristic)?;
+    let mut depth = heuristic.estimate(&position.distances);
+    while depth <= max_depth {
+        let mut solutions = Vec::new();
+        search(&position, depth, &context, &mut Vec::new(), None, &mut solutions);
+        if !solutions.is_empty() {
+            return Ok(solutions);
+        }
+        depth += 1;
+    }
+    Ok(Vec::new())
+}
+
+/// Yields every solution in order of length, from the optimal length up to slack turns above it.
+/// Solutions never pass through a solved position before their last turn. The solutions of each
+/// length are only searched for once the shorter ones have been used up.
+pub struct SolutionIter<'a> {
+    position: Position,
+    tables: Vec<IndexedTables<'a>>,
+    heuristic: Box<dyn Heuristic + 'a>,
+    successors: SuccessorTable,
+    slack: u8,
+    depth: u8,
+    max_depth: u8,
+    solutions: VecDeque<Vec<Turn>>,
+}
+
+impl<'a> SolutionIter<'a> {
+    pub fn new(coords: &[usize], tables: &[SearchTables<'a>], slack: u8) -> Result<Self> {
+        let move_tables: Vec<&MoveTables> = tables.iter().map(|(move_tables, _)| *move_tables).collect();
+        Self::new_with_heuristic(coords, &move_tables, Box::new(MaxHeuristic::from_tables(tables)), slack)
+    }
+
+    /// Searches with any heuristic for the coordinates, which are moved by the move tables at the same index
+    pub fn new_with_heuristic(coords: &[usize], move_tables: &[&'a MoveTables], heuristic: Box<dyn Heuristic + 'a>, slack: u8) -> Result<Self> {
+        let tables = get_indexed_tables(move_tables)?;
+        let position = get_position(coords, heuristic.as_ref())?;
+        Ok(Self {
+            depth: heuristic.estimate(&position.distances),
+            position,
+            tables,
+            heuristic,
+            successors: SuccessorTable::new(move_tables[0].get_turns()),
+            slack,
+            max_depth: MAX_SOLUTION_LENGTH,
+            solutions: VecDeque::new(),
+        })
+    }
+}
+
+impl Iterator for SolutionIter<'_> {
+    type Item = Vec<Turn>;
+
+    fn next(&mut self) -> Option<Vec<Turn>> {
+        while self.solutions.is_empty() {
+            if self.depth > self.max_depth {
+                return None;
+            }
+            let context = SearchContext {
+                tables: &self.tables,
+                heuristic: self.heuristic.as_ref(),
+                successors: &self.successors,
+            };
+            let mut solutions = Vec::new();
+            search(&self.position, self.depth, &context, &mut Vec::new(), None, &mut solutions);
+
+            // The first solutions found are optimal, which sets how far the search goes
+            if !solutions.is_empty() && self.max_depth == MAX_SOLUTION_LENGTH {
+                self.max_depth = self.depth.saturating_add(self.slack).min(MAX_SOLUTION_LENGTH);
             }
+            self.solutions.extend(solutions);
+            self.depth += 1;
         }
+        self.solutions.pop_front()
+    }
+}
+
+/// Returns an iterator over every solution of up to slack turns more than optimal, shortest first
+pub fn iter_solutions<'a>(coord: usize, movetables: &'a MoveTables, pruningtable: &'a PruningTable, slack: u8) -> Result<SolutionIter<'a>> {
+    SolutionIter::new(&[coord], &[(movetables, pruningtable)], slack)
+}
+
+fn get_position(coords: &[usize], heuristic: &dyn Heuristic) -> Result<Position> {
+    let mut distances = vec![0; heuristic.get_num_distances()];
+    heuristic.get_initial_distances(coords, &mut distances)?;
+    Ok(Position {
+        coords: coords.to_vec(),
+        distances,
+    })
+}
+
+// Fails if there are no move tables, or some move tables lack a turn of the first
+fn get_indexed_tables<'a>(move_tables: &[&'a MoveTables]) -> Result<Vec<IndexedTables<'a>>> {
+    let turns = move_tables.first().ok_or(Error::NoMoveTables)?.get_turns();
+    move_tables.iter().map(|move_tables| Ok(IndexedTables {
+        move_tables,
+        turn_indices: turns.iter()
+            .map(|turn| move_tables.get_turn_index(turn).ok_or(Error::MissingMoveTable(*turn)))
+            .collect::<Result<Vec<usize>>>()?,
+    })).collect()
+}
+
+// Depth first search for every solution of exactly remaining turns
+fn search(position: &Position, remaining: u8, context: &SearchContext, solution: &mut Vec<Turn>,
+        last_turn: Option<usize>, solutions: &mut Vec<Vec<Turn>>) {
+    let estimate = context.heuristic.estimate(&position.distances);
+    if estimate == 0 {
+        if remaining == 0 {
+            solutions.push(solution.clone());
+        }
+        return;
+    }
+    if estimate > remaining {
+        return;
+    }
+
+    let mut new_position = position.clone();
+    for turn_index in context.successors.get_successors(last_turn) {
+        let turn = context.successors.get_turn(*turn_index);
+        for (i, indexed_tables) in context.tables.iter().enumerate() {
+            new_position.coords[i] = indexed_tables.move_tables.apply_move_index_to_coord(position.coords[i], indexed_tables.turn_indices[*turn_index]);
+        }
+        context.heuristic.get_distances_after_turn(&new_position.coords, &position.distances, &mut new_position.distances);
+        solution.push(*turn);
+        search(&new_position, remaining - 1, context, solution, Some(*turn_index), solutions);
+        solution.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::{Coordinate, BasicCoordinate};
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::e_slice_edge_sep::ESliceEdgeSepCoord;
+    use crate::heuristic::TableHeuristic;
+    use crate::moveset::MoveSet;
+    use crate::rawcube::RawState;
+    use crate::tables::pruningtables::PruningEncoding;
+
+    #[test]
+    fn test_ida_star() {
+        let eo = EOFBCoord::new();
+        let co = COUDCoord::new();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let eo_pruning_table = PruningTable::new(eo, &eo_move_tables).unwrap();
+        let co_pruning_table = PruningTable::new(co, &co_move_tables).unwrap();
+        let tables = [(&eo_move_tables, &eo_pruning_table), (&co_move_tables, &co_pruning_table)];
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("F R U' B L2 D"));
+        let coords = [eo.convert_raw_state_to_coord(&scramble), co.convert_raw_state_to_coord(&scramble)];
+
+        let solutions = ida_star(&coords, &tables, 20).unwrap();
+        assert!(!solutions.is_empty());
+        let optimal_length = solutions[0].len();
+        let heuristic = MaxHeuristic::from_tables(&tables);
+        assert!(optimal_length as u8 >= heuristic.estimate(&get_position(&coords, &heuristic).unwrap().distances));
+
+        for solution in &solutions {
+            assert_eq!(solution.len(), optimal_length);
+            for pair in solution.windows(2) {
+                assert!(pair[1].can_follow(&pair[0]));
+            }
+            let mut state = scramble.clone();
+            state.apply_algorithm(solution);
+            assert_eq!(eo.convert_raw_state_to_coord(&state), 0);
+            assert_eq!(co.convert_raw_state_to_coord(&state), 0);
+        }
+
+        // Nothing shorter exists
+        assert!(ida_star(&coords, &tables, optimal_length as u8 - 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_iter_solutions() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(co, &move_tables).unwrap();
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("R F' L B"));
+        let coord = co.convert_raw_state_to_coord(&scramble);
+        let distance = pruning_table.get_distance(coord).unwrap() as usize;
+
+        let solutions: Vec<Vec<Turn>> = iter_solutions(coord, &move_tables, &pruning_table, 1).unwrap().collect();
+        assert_eq!(solutions[0].len(), distance);
+        assert!(solutions.iter().any(|solution| solution.len() == distance + 1));
+        assert!(solutions.windows(2).all(|pair| pair[0].len() <= pair[1].len()));
+        assert!(solutions.iter().all(|solution| solution.len() <= distance + 1));
+
+        for (i, solution) in solutions.iter().enumerate() {
+            assert!(!solutions[..i].contains(solution));
+            let mut state = scramble.clone();
+            state.apply_algorithm(solution);
+            assert_eq!(co.convert_raw_state_to_coord(&state), 0);
+        }
+
+        let solved_coord = co.convert_raw_state_to_coord(&RawState::solved());
+        let solutions: Vec<Vec<Turn>> = iter_solutions(solved_coord, &move_tables, &pruning_table, 2).unwrap().collect();
+        assert_eq!(solutions, vec![Vec::<Turn>::new()]);
+    }
+
+    #[test]
+    fn test_compact_pruning_tables() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(co, &move_tables).unwrap();
+        let mod3_table = PruningTable::new_with_encoding(co, &move_tables, PruningEncoding::Mod3).unwrap();
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("R F' L B U2 R"));
+        let coord = co.convert_raw_state_to_coord(&scramble);
+        let solutions: Vec<Vec<Turn>> = iter_solutions(coord, &move_tables, &pruning_table, 1).unwrap().collect();
+        let mod3_solutions: Vec<Vec<Turn>> = iter_solutions(coord, &move_tables, &mod3_table, 1).unwrap().collect();
+        assert!(!solutions.is_empty());
+        assert_eq!(mod3_solutions, solutions);
+    }
+
+    #[test]
+    fn test_solve_optimally() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(co, &move_tables).unwrap();
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("R F' L B"));
+        let coord = co.convert_raw_state_to_coord(&scramble);
+        let distance = pruning_table.get_distance(coord).unwrap();
+
+        let solution = solve_optimally(coord, move_tables, pruning_table).unwrap();
+        assert_eq!(solution.len(), distance as usize);
+        scramble.apply_algorithm(&solution);
+        assert_eq!(co.convert_raw_state_to_coord(&scramble), 0);
+    }
+
+    #[test]
+    fn test_heuristic_search() {
+        // Searching towards DR with the largest of the CO, EO and E slice distances
+        let eo = EOFBCoord::new();
+        let co = COUDCoord::new();
+        let e_slice = ESliceEdgeSepCoord::new();
+        let move_set = MoveSet::full();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &move_set).unwrap();
+        let co_move_tables = MoveTables::new_basic_table(co, &move_set).unwrap();
+        let e_slice_move_tables = MoveTables::new_basic_table(e_slice, &move_set).unwrap();
+        let eo_pruning_table = PruningTable::new(eo, &eo_move_tables).unwrap();
+        let co_pruning_table = PruningTable::new(co, &co_move_tables).unwrap();
+        let e_slice_pruning_table = PruningTable::new(e_slice, &e_slice_move_tables).unwrap();
+        let heuristic = MaxHeuristic::new(vec![
+            Box::new(TableHeuristic::new(0, &eo_move_tables, &eo_pruning_table)),
+            Box::new(TableHeuristic::new(1, &co_move_tables, &co_pruning_table)),
+            Box::new(TableHeuristic::new(2, &e_slice_move_tables, &e_slice_pruning_table)),
+        ]);
+        let move_tables = [&eo_move_tables, &co_move_tables, &e_slice_move_tables];
+
+        let mut scramble = RawState::solved();
+        scramble.apply_algorithm(&Turn::get_vec_from_alg_string("R U F' D"));
+        let coords = [eo.convert_raw_state_to_coord(&scramble), co.convert_raw_state_to_coord(&scramble),
+            e_slice.convert_raw_state_to_coord(&scramble)];
+        let solutions = ida_star_with_heuristic(&coords, &move_tables, &heuristic, 10).unwrap();
+        assert!(!solutions.is_empty());
+        let iter_solutions: Vec<Vec<Turn>> = SolutionIter::new_with_heuristic(&coords, &move_tables, Box::new(heuristic), 0).unwrap().collect();
+        assert_eq!(iter_solutions, solutions);
+        for solution in &solutions {
+            let mut state = scramble.clone();
+            state.apply_algorithm(solution);
+            assert_eq!(eo.convert_raw_state_to_coord(&state), 0);
+            assert_eq!(co.convert_raw_state_to_coord(&state), 0);
+            assert_eq!(e_slice.convert_raw_state_to_coord(&state), e_slice.convert_raw_state_to_coord(&RawState::solved()));
+        }
+    }
+
+    #[test]
+    fn test_search_errors() {
+        let heuristic = MaxHeuristic::new(Vec::new());
+        assert_eq!(ida_star_with_heuristic(&[], &[], &heuristic, 10), Err(Error::NoMoveTables));
+        assert!(matches!(SolutionIter::new(&[], &[], 0), Err(Error::NoMoveTables)));
+
+        // The second coordinate's move tables lack the quarter turns of F and B used by the first
+        let co = COUDCoord::new();
+        let eo = EOFBCoord::new();
+        let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &MoveSet::eo_fb()).unwrap();
+        let result = ida_star_with_heuristic(&[0, 0], &[&co_move_tables, &eo_move_tables], &heuristic, 10);
+        assert!(matches!(result, Err(Error::MissingMoveTable(turn)) if turn.is_same_axis(&Turn::FRONT)));
     }
-    solution
-}
\ No newline at end of file
+}
@@ -0,0 +1,401 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+
+use crate::error::{self, Error};
+use crate::moveset::MoveSet;
+use crate::tables::movetables::MoveTables;
+use crate::tables::pruningtables::{PruningTable, PruningEncoding, GoalSet};
+use crate::turndef::Turn;
+
+/*
+Tables are saved in a binary file made of a header followed by the table itself. All numbers are little endian.
+    8 bytes: magic bytes "CUBETABL"
+    u32: format version, increased whenever the layout of any table changes
+    u8: table kind, 0 for move tables, and 1, 2 or 3 for pruning tables stored as bytes, nibbles or distances modulo 3
+    u16 + bytes: coordinate identity, such as "EOFBCoord"
+    u16 + u32s: move set, sorted
+    u64: number of coordinates
+    u64: length of the table in bytes
+    u64: FNV-1a checksum of the table
+A cached table is only used if its header matches the table being asked for and its checksum is correct.
+Otherwise it is generated again and the file is replaced.
+
+Pruning tables are memory-mapped read-only rather than copied into memory, so processes solving at the same
+time share a single copy of each table. Files are never changed once written, only replaced by renaming a new
+file over them, so a mapped table stays valid even if another process regenerates it.
+*/
+
+const MAGIC: &[u8; 8] = b"CUBETABL";
+const FORMAT_VERSION: u32 = 2;
+
+// Overrides the directory tables are cached in
+const CACHE_DIR_VARIABLE: &str = "CUBE_SOLVER_CACHE_DIR";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TableKind {
+    Move,
+    Pruning(PruningEncoding),
+}
+
+impl TableKind {
+    fn get_id(&self) -> u8 {
+        match self {
+            TableKind::Move => 0,
+            TableKind::Pruning(PruningEncoding::Byte) => 1,
+            TableKind::Pruning(PruningEncoding::Nibble) => 2,
+            TableKind::Pruning(PruningEncoding::Mod3) => 3,
+        }
+    }
+}
+
+/// Describes which table a file holds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableHeader {
+    pub kind: TableKind,
+    pub identity: String,
+    pub move_set: MoveSet,
+    pub size: usize,
+}
+
+impl TableHeader {
+    pub fn new(kind: TableKind, identity: &str, move_set: &[Turn], size: usize) -> Self {
+        Self {
+            kind,
+            identity: identity.to_string(),
+            move_set: MoveSet::new(move_set),
+            size,
+        }
+    }
+
+    pub fn get_file_name(&self) -> String {
+        let extension = match self.kind {
+            TableKind::Move => "move",
+            TableKind::Pruning(PruningEncoding::Byte) => "prune",
+            TableKind::Pruning(PruningEncoding::Nibble) => "prune4",
+            TableKind::Pruning(PruningEncoding::Mod3) => "prune2",
+        };
+        format!("{}-{:016x}.{}", self.identity, checksum(&get_sorted_turn_bytes(&self.move_set)), extension)
+    }
+
+    fn to_bytes(&self, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        bytes.push(self.kind.get_id());
+        bytes.extend((self.identity.len() as u16).to_le_bytes());
+        bytes.extend(self.identity.as_bytes());
+        bytes.extend((self.move_set.len() as u16).to_le_bytes());
+        bytes.extend(get_sorted_turn_bytes(&self.move_set));
+        bytes.extend((self.size as u64).to_le_bytes());
+        bytes.extend((payload.len() as u64).to_le_bytes());
+        bytes.extend(checksum(payload).to_le_bytes());
+        bytes
+    }
+}
+
+/// Reads numbers from a table file, failing instead of panicking if the file is too short
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+        }
+    }
+
+    pub fn read_bytes(&mut self, length: usize) -> error::Result<&'a [u8]> {
+        let end = self.position.checked_add(length).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| Error::InvalidTableData("Unexpected end of table".to_string()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> error::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> error::Result<u16> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn read_u32(&mut self) -> error::Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> error::Result<u64> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+}
+
+/// Returns the directory tables are cached in. This can be set with the CUBE_SOLVER_CACHE_DIR
+/// environment variable, and otherwise follows the usual cache directory of the platform.
+pub fn get_cache_dir() -> PathBuf {
+    if let Some(dir) = env::var_os(CACHE_DIR_VARIABLE) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("cube-solver");
+    }
+    if let Some(dir) = env::var_os("HOME") {
+        return PathBuf::from(dir).join(".cache").join("cube-solver");
+    }
+    env::temp_dir().join("cube-solver")
+}
+
+/// Writes a table file, replacing any existing file only once the new one is complete
+pub fn write_table_file(path: &Path, header: &TableHeader, payload: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut bytes = header.to_bytes(payload);
+    bytes.extend(payload);
+
+    let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&temp_path, bytes)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Reads the table from a table file, checking that the file holds the expected table and is not corrupt
+pub fn read_table_file(path: &Path, expected: &TableHeader) -> error::Result<Vec<u8>> {
+    let bytes = fs::read(path).map_err(|error| get_read_error(path, &error))?;
+    let payload = find_payload(&bytes, expected)?;
+    Ok(bytes[payload].to_vec())
+}
+
+/// Maps a table file into memory read-only, returning the map and where the table is within it.
+/// The file is checked in the same way as by read_table_file.
+pub fn map_table_file(path: &Path, expected: &TableHeader) -> error::Result<(Mmap, Range<usize>)> {
+    let file = fs::File::open(path).map_err(|error| get_read_error(path, &error))?;
+    // Table files are replaced rather than modified, so the mapped contents can not change under us
+    let map = unsafe { Mmap::map(&file) }.map_err(|error| get_read_error(path, &error))?;
+    let payload = find_payload(&map, expected)?;
+    Ok((map, payload))
+}
+
+/// Checks the header and checksum of a table file, returning where the table is in the file
+pub fn find_payload(bytes: &[u8], expected: &TableHeader) -> error::Result<Range<usize>> {
+    let mut reader = ByteReader::new(bytes);
+
+    if reader.read_bytes(MAGIC.len())? != MAGIC {
+        return Err(Error::InvalidTableData("Not a table file".to_string()));
+    }
+    let version = reader.read_u32()?;
+    if version != FORMAT_VERSION {
+        return Err(Error::InvalidTableData(format!("Table file has format version {}, expected {}", version, FORMAT_VERSION)));
+    }
+
+    let kind = reader.read_u8()?;
+    let identity_length = reader.read_u16()? as usize;
+    let identity = String::from_utf8_lossy(reader.read_bytes(identity_length)?).to_string();
+    let mut move_set = Vec::new();
+    for _ in 0..reader.read_u16()? {
+        move_set.push(reader.read_u32()?.into());
+    }
+    let size = reader.read_u64()? as usize;
+    if kind != expected.kind.get_id() || identity != expected.identity || MoveSet::new(&move_set) != expected.move_set || size != expected.size {
+        return Err(Error::InvalidTableData("Table file holds a different table".to_string()));
+    }
+
+    let payload_length = reader.read_u64()? as usize;
+    let expected_checksum = reader.read_u64()?;
+    let start = reader.get_position();
+    let payload = reader.read_bytes(payload_length)?;
+    if !reader.is_empty() || checksum(payload) != expected_checksum {
+        return Err(Error::InvalidTableData("Table file is corrupt".to_string()));
+    }
+    Ok(start..start + payload_length)
+}
+
+/// Loads move tables from the cache in dir, or generates and caches them if there is no valid cached copy.
+/// The move set and size are used to check the cached copy is current. Tables that can not be saved are
+/// still returned, along with the error from saving them.
+pub fn load_or_generate_move_tables(dir: &Path, identity: &str, move_set: &[Turn], size: usize,
+        generate: impl FnOnce() -> error::Result<MoveTables>) -> error::Result<(MoveTables, Option<io::Error>)> {
+    let header = TableHeader::new(TableKind::Move, identity, move_set, size);
+    let path = dir.join(header.get_file_name());
+    if let Ok(tables) = read_table_file(&path, &header).and_then(|payload| MoveTables::from_bytes(&payload, size)) {
+        return Ok((tables, None));
+    }
+
+    let tables = generate()?;
+    let write_error = write_table_file(&path, &header, &tables.to_bytes()).err();
+    Ok((tables, write_error))
+}
+
+/// Maps a pruning table from the cache in dir, or generates and caches it if there is no valid cached copy.
+/// A newly generated table is mapped from its file once saved, so every process shares the same pages.
+/// The generated table must use the given encoding and goal set, and a cached copy for other goals is replaced.
+/// Tables that can not be saved are still returned, along with the error from saving them.
+pub fn load_or_generate_pruning_table(dir: &Path, identity: &str, move_set: &[Turn], size: usize, encoding: PruningEncoding, goals: &GoalSet,
+        generate: impl FnOnce() -> error::Result<PruningTable>) -> error::Result<(PruningTable, Option<io::Error>)> {
+    let header = TableHeader::new(TableKind::Pruning(encoding), identity, move_set, size);
+    let path = dir.join(header.get_file_name());
+    let map_table = || map_table_file(&path, &header)
+        .and_then(|(map, payload)| PruningTable::from_mapped(map, payload, size, move_set, encoding))
+        .and_then(|table| match table.get_goal_coords() == goals.get_coords() {
+            true => Ok(table),
+            false => Err(Error::InvalidTableData("Table file holds a table for different goals".to_string())),
+        });
+    if let Ok(table) = map_table() {
+        return Ok((table, None));
+    }
+
+    let table = generate()?;
+    if let Err(error) = write_table_file(&path, &header, &table.to_bytes()) {
+        return Ok((table, Some(error)));
+    }
+    match map_table() {
+        Ok(mut mapped) => {
+            mapped.copy_generation_details(&table);
+            Ok((mapped, None))
+        },
+        Err(_) => Ok((table, None)),
+    }
+}
+
+fn get_read_error(path: &Path, error: &io::Error) -> Error {
+    Error::UnreadableTableFile(format!("{}: {}", path.display(), error))
+}
+
+fn get_sorted_turn_bytes(move_set: &MoveSet) -> Vec<u8> {
+    let mut turns: Vec<u32> = move_set.iter().map(|turn| turn.into()).collect();
+    turns.sort();
+    turns.iter().flat_map(|turn| turn.to_le_bytes()).collect()
+}
+
+// 64 bit FNV-1a hash
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::Coordinate;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+
+    // A directory of its own for each test, which the test removes when it is done
+    fn get_temp_dir(test_name: &str) -> PathBuf {
+        env::temp_dir().join(format!("cube-solver-{}-{}", test_name, std::process::id()))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let eo = EOFBCoord::new();
+        let move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(eo, &move_tables).unwrap();
+
+        let dir = get_temp_dir("round-trip");
+        let path = dir.join("eo.move");
+        let header = TableHeader::new(TableKind::Move, "EOFBCoord", move_tables.get_turns(), eo.get_size());
+        write_table_file(&path, &header, &move_tables.to_bytes()).unwrap();
+        let loaded = MoveTables::from_bytes(&read_table_file(&path, &header).unwrap(), eo.get_size()).unwrap();
+        assert_eq!(MoveSet::new(loaded.get_turns()), MoveSet::new(move_tables.get_turns()));
+        for coord in 0..eo.get_size() {
+            for turn in &eo.get_allowed_turns() {
+                assert_eq!(loaded.apply_move_to_coord(coord, turn).unwrap(), move_tables.apply_move_to_coord(coord, turn).unwrap());
+            }
+        }
+
+        let path = dir.join("eo.prune");
+        let header = TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "EOFBCoord", move_tables.get_turns(), eo.get_size());
+        write_table_file(&path, &header, &pruning_table.to_bytes()).unwrap();
+        let payload = read_table_file(&path, &header).unwrap();
+        let loaded = PruningTable::from_bytes(&payload, eo.get_size(), move_tables.get_turns(), PruningEncoding::Byte).unwrap();
+        for coord in 0..eo.get_size() {
+            assert_eq!(loaded.get_distance(coord).unwrap(), pruning_table.get_distance(coord).unwrap());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_pruning_table() {
+        let eo = EOFBCoord::new();
+        let move_tables = MoveTables::new_basic_table(eo, &MoveSet::full()).unwrap();
+        let generated = PruningTable::new(eo, &move_tables).unwrap();
+
+        let dir = get_temp_dir("mapped");
+        let load = |goals: &GoalSet| load_or_generate_pruning_table(&dir, "EOFBCoord", move_tables.get_turns(), eo.get_size(), PruningEncoding::Byte,
+            goals, || PruningTable::new_with_goals(eo, &move_tables, PruningEncoding::Byte, goals)).unwrap().0;
+        let first = load(&GoalSet::solved(eo));
+        let second = load(&GoalSet::solved(eo));
+        assert!(first.is_memory_mapped());
+        assert!(second.is_memory_mapped());
+        for coord in 0..eo.get_size() {
+            assert_eq!(first.get_distance(coord).unwrap(), generated.get_distance(coord).unwrap());
+            assert_eq!(second.get_distance(coord).unwrap(), generated.get_distance(coord).unwrap());
+        }
+
+        // A cached table for other goals is generated again
+        let goals = GoalSet::from_coords(&[0, 1]);
+        let regenerated = load(&goals);
+        assert_eq!(regenerated.get_goal_coords(), goals.get_coords());
+        assert_eq!(regenerated.get_distance(1).unwrap(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unwritable_cache() {
+        // A file where the cache directory should be means no table can be saved there
+        let dir = get_temp_dir("unwritable");
+        let file = dir.join("not-a-dir");
+        write_table_file(&file, &TableHeader::new(TableKind::Move, "Test", &MoveSet::full(), 0), &[]).unwrap();
+        let eo = EOFBCoord::new();
+        let (tables, write_error) = load_or_generate_move_tables(&file, "EOFBCoord", &MoveSet::full(), eo.get_size(),
+            || MoveTables::new_basic_table(eo, &MoveSet::full())).unwrap();
+        assert_eq!(tables.get_size(), eo.get_size());
+        assert!(write_error.is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_and_corrupt_files() {
+        let dir = get_temp_dir("stale");
+        let path = dir.join("table");
+        let turns = MoveSet::full();
+        let header = TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "Test", &turns, 4);
+        write_table_file(&path, &header, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(read_table_file(&path, &header).unwrap(), vec![1, 2, 3, 4]);
+
+        assert!(read_table_file(&path, &TableHeader::new(TableKind::Move, "Test", &turns, 4)).is_err());
+        assert!(read_table_file(&path, &TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "Other", &turns, 4)).is_err());
+        assert!(read_table_file(&path, &TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "Test", &MoveSet::ru(), 4)).is_err());
+        assert!(read_table_file(&path, &TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "Test", &turns, 5)).is_err());
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(read_table_file(&path, &header).is_err());
+
+        fs::write(&path, &bytes[..10]).unwrap();
+        assert!(matches!(read_table_file(&path, &header), Err(Error::InvalidTableData(_))));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(read_table_file(&path, &header), Err(Error::UnreadableTableFile(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
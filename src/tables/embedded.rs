@@ -0,0 +1,81 @@
+use crate::error;
+use crate::tables::cache::{self, TableHeader};
+
+/*
+With the embedded-tables feature, the build script generates the move and pruning tables of the small coordinates
+(EO, CO, corner permutation and the E, M and S slice edge permutations) over their own turns, and they are compiled
+into the binary. They are
+stored as table files in the format of the disk cache, named by their headers in the same way.
+*/
+
+#[cfg(feature = "embedded-tables")]
+include!(concat!(env!("OUT_DIR"), "/embedded_tables.rs"));
+
+#[cfg(not(feature = "embedded-tables"))]
+static EMBEDDED_TABLES: &[(&str, &[u8])] = &[];
+
+/// Returns the table described by the header if it is embedded in the binary. An embedded table whose
+/// header does not match gives an error, as it was built from different table code.
+pub fn get_embedded_table(header: &TableHeader) -> error::Result<Option<&'static [u8]>> {
+    let file_name = header.get_file_name();
+    let Some((_, bytes)) = EMBEDDED_TABLES.iter().find(|(name, _)| *name == file_name) else {
+        return Ok(None);
+    };
+    let payload = cache::find_payload(bytes, header)?;
+    Ok(Some(&bytes[payload]))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::{Coordinate, BasicCoordinate};
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::cp::CornerPermCoord;
+    use crate::coords::basic_coords::e_m_s_edges::{ESliceEdgePermCoord, MSliceEdgePermCoord, SSliceEdgePermCoord};
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::tables::cache::TableKind;
+    use crate::tables::movetables::MoveTables;
+    use crate::tables::pruningtables::{PruningTable, PruningEncoding};
+
+    #[test]
+    fn test_embedded_tables() {
+        let eo = EOFBCoord::new();
+        let move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let pruning_table = PruningTable::new(eo, &move_tables).unwrap();
+        let move_header = TableHeader::new(TableKind::Move, "EOFBCoord", move_tables.get_turns(), eo.get_size());
+        let pruning_header = TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), "EOFBCoord", move_tables.get_turns(), eo.get_size());
+        if !cfg!(feature = "embedded-tables") {
+            assert_eq!(get_embedded_table(&move_header), Ok(None));
+            return;
+        }
+
+        let embedded_move_tables = MoveTables::from_bytes(get_embedded_table(&move_header).unwrap().unwrap(), eo.get_size()).unwrap();
+        assert_eq!(embedded_move_tables.to_bytes(), move_tables.to_bytes());
+        let embedded_pruning_table = PruningTable::from_static(get_embedded_table(&pruning_header).unwrap().unwrap(),
+            eo.get_size(), move_tables.get_turns(), PruningEncoding::Byte).unwrap();
+        assert_eq!(embedded_pruning_table.to_bytes(), pruning_table.to_bytes());
+    }
+
+    // Whether both tables of a coordinate that the solver asks the registry for are embedded
+    fn is_embedded<C: BasicCoordinate>(coord_type: C) -> bool {
+        let identity = format!("{:?}", coord_type);
+        let turns = coord_type.get_allowed_turns();
+        let move_header = TableHeader::new(TableKind::Move, &identity, &turns, coord_type.get_size());
+        let pruning_header = TableHeader::new(TableKind::Pruning(PruningEncoding::Byte), &identity, &turns, coord_type.get_size());
+        matches!(get_embedded_table(&move_header), Ok(Some(_))) && matches!(get_embedded_table(&pruning_header), Ok(Some(_)))
+    }
+
+    #[test]
+    fn test_solver_tables_are_embedded() {
+        let embedded = [
+            is_embedded(EOFBCoord::new()),
+            is_embedded(COUDCoord::new()),
+            is_embedded(CornerPermCoord::new()),
+            is_embedded(ESliceEdgePermCoord::new()),
+            is_embedded(MSliceEdgePermCoord::new()),
+            is_embedded(SSliceEdgePermCoord::new()),
+        ];
+        assert!(embedded.iter().all(|is_embedded| *is_embedded == cfg!(feature = "embedded-tables")));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod cache;
+pub mod embedded;
 pub mod movetables;
+pub mod progress;
 pub mod pruningtables;
+pub mod registry;
+pub mod successortables;
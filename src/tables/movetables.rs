@@ -1,79 +1,111 @@
-use std::collections::HashMap;
-use std::hash::BuildHasherDefault;
+use std::time::Instant;
 
-use nohash_hasher::NoHashHasher;
 use rayon::prelude::*;
 
-use crate::coords::{Coordinate, BasicCoordinate};
-use crate::rawcube::TurnEffect;
+use crate::coords::BasicCoordinate;
+use crate::error::{self, Error};
+use crate::tables::cache::ByteReader;
+use crate::tables::progress::GenerationOptions;
 use crate::turndef::{Turn, Algorithm};
 
-/// MoveTable maps how a specific turn changes a coordinate
+/*
+MoveTables stores how every turn changes a coordinate in a single flat table, where the new coordinates
+after each turn are stored next to each other for every coordinate. Each stored turn has an index, and
+the entry for a coordinate and turn index is at coord * number of turns + index. The turns offered to a
+search come first, so they have the indices 0 to get_turns().len() - 1, followed by any base turns that
+were only needed to build compound turns. Searches should find the index of each turn once and use
+apply_move_index_to_coord, rather than looking up the turn for every coordinate.
+*/
+
+/// MoveTable maps how a specific turn changes a coordinate, used while building MoveTables
 struct MoveTable {
     table: Vec<usize>,
 }
 
 impl MoveTable {
-    fn generate_from_base_turn<C: BasicCoordinate>(coord_type: C, turn: &Turn) -> Self {
-        let mut table = vec![usize::MAX; coord_type.get_size()];
-
-        for coord in 0..coord_type.get_size() {
-            table[coord] = coord;
-        }
-
-        table.par_iter_mut().for_each(|coord| {
-            let new_coord = C::apply_raw_turn(*coord, &turn);
-            *coord = new_coord;
-        });
+    fn generate_from_base_turn<C: BasicCoordinate>(coord_type: C, turn: &Turn) -> error::Result<Self> {
+        let table = (0..coord_type.get_size()).into_par_iter()
+            .map(|coord| coord_type.apply_turn(coord, turn))
+            .collect::<error::Result<Vec<usize>>>()?;
 
-        // for coord in 0..coord_type.get_size() {
-        //     let new_coord = C::apply_raw_turn(coord, &turn);
-        //     table[coord] = new_coord;
-        // }
-
-        Self {
+        Ok(Self {
             table,
-        }
+        })
     }
 }
 
 impl MoveTable {
-    fn generate_from_compound_turn(turn: &Turn, size: usize, move_tables: &MoveTables) -> Self {
-        let mut table = vec![usize::MAX; size];
-        let base_turns = turn.to_base_turns();
+    fn generate_from_compound_turn(turn: &Turn, size: usize, base_tables: &[(Turn, MoveTable)]) -> error::Result<Self> {
+        let base_tables = turn.to_base_turns().iter()
+            .map(|base_turn| base_tables.iter()
+                .find(|(turn, _)| turn == base_turn)
+                .map(|(_, table)| table)
+                .ok_or(Error::MissingMoveTable(*base_turn)))
+            .collect::<error::Result<Vec<&MoveTable>>>()?;
 
-        for coord in 0..size {
-            let mut new_coord = coord;
-            for base_turn in &base_turns {
-                new_coord = move_tables.apply_move_to_coord(new_coord, base_turn);
-            }
-            table[coord] = new_coord;
-        }
-        Self {
+        let mut table = vec![usize::MAX; size];
+        table.par_iter_mut().enumerate().for_each(|(coord, new_coord)| {
+            *new_coord = base_tables.iter().fold(coord, |coord, base_table| base_table.table[coord]);
+        });
+        Ok(Self {
             table,
-        }
+        })
     }
 }
 
 /// MoveTables maps how each turn from a set of turns changes a coordinate
 pub struct MoveTables {
-    table: HashMap<Turn, MoveTable, BuildHasherDefault<NoHashHasher<usize>>>,
+    table: Vec<u32>,
     turns: Vec<Turn>,
+    num_search_turns: usize,
+    size: usize,
 }
 
 impl MoveTables {
-    fn empty() -> Self {
+    // Lays out the table of each turn in one flat table, with the turns offered to a search first
+    fn from_turn_tables(mut turn_tables: Vec<(Turn, MoveTable)>, search_turns: &[Turn], size: usize) -> Self {
+        turn_tables.sort_by_key(|(turn, _)| search_turns.iter().position(|search_turn| search_turn == turn).unwrap_or(usize::MAX));
+        let num_turns = turn_tables.len();
+
+        let mut table = vec![0; size * num_turns];
+        if num_turns > 0 {
+            table.par_chunks_mut(num_turns).enumerate().for_each(|(coord, new_coords)| {
+                for (index, (_, turn_table)) in turn_tables.iter().enumerate() {
+                    new_coords[index] = turn_table.table[coord] as u32;
+                }
+            });
+        }
+
         Self {
-            table: HashMap::with_hasher(BuildHasherDefault::default()),
-            turns: Vec::new(),
+            table,
+            turns: turn_tables.into_iter().map(|(turn, _)| turn).collect(),
+            num_search_turns: search_turns.len(),
+            size,
         }
     }
 
     pub fn get_turns(&self) -> &[Turn] {
-        &self.turns
+        &self.turns[..self.num_search_turns]
+    }
+
+    /// The number of coordinates the tables cover
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the index of a turn for use with apply_move_index_to_coord. The turns from get_turns
+    /// have the same indices as their positions in it.
+    pub fn get_turn_index(&self, turn: &Turn) -> Option<usize> {
+        self.turns.iter().position(|stored_turn| stored_turn == turn)
     }
 
-    fn generate_base_tables<C: BasicCoordinate>(&mut self, coord_type: C, move_set: &[Turn]) {
+    pub fn new_basic_table<C: BasicCoordinate>(coord_type: C, move_set: &[Turn]) -> error::Result<Self> {
+        Self::new_basic_table_with_options(coord_type, move_set, &GenerationOptions::default())
+    }
+
+    /// Builds basic tables, reporting progress after the table of each turn and stopping if cancelled
+    pub fn new_basic_table_with_options<C: BasicCoordinate>(coord_type: C, move_set: &[Turn], options: &GenerationOptions) -> error::Result<Self> {
+        let start = Instant::now();
         let mut base_turns = Vec::new();
         for turn in move_set.to_base_turns() {
             if turn.is_base_move() && !base_turns.contains(&turn) {
@@ -81,32 +113,244 @@ impl MoveTables {
             }
         }
 
+        // Base turns are always needed to build compound turns, but only
+        // the turns in the move set should be offered to a search
+        let size = coord_type.get_size();
+        let num_compound_turns = move_set.iter().filter(|turn| !turn.is_base_move()).count();
+        let total = (base_turns.len() + num_compound_turns) * size;
+        let mut turn_tables = Vec::new();
+        let mut search_turns = Vec::new();
         for turn in base_turns {
-            self.table.insert(turn, MoveTable::generate_from_base_turn(coord_type, &turn));
-            self.turns.push(turn);
+            options.check_cancelled()?;
+            turn_tables.push((turn, MoveTable::generate_from_base_turn(coord_type, &turn)?));
+            options.report(turn_tables.len() * size, total, None, start);
+            if move_set.contains(&turn) {
+                search_turns.push(turn);
+            }
         }
-    }
 
-    fn generate_compound_tables(&mut self, size: usize, move_set: &[Turn]) {
+        let mut compound_tables = Vec::new();
         for turn in move_set {
             if !turn.is_base_move() {
-                self.table.insert(*turn, MoveTable::generate_from_compound_turn(turn, size, self));
-                self.turns.push(*turn);
+                options.check_cancelled()?;
+                compound_tables.push((*turn, MoveTable::generate_from_compound_turn(turn, size, &turn_tables)?));
+                options.report((turn_tables.len() + compound_tables.len()) * size, total, None, start);
+                search_turns.push(*turn);
+            }
+        }
+        turn_tables.extend(compound_tables);
+        Ok(Self::from_turn_tables(turn_tables, &search_turns, coord_type.get_size()))
+    }
+
+    /// Builds move tables for a coordinate that is only defined within a subgroup, such as half turn
+    /// reduction within domino reduction. The base turns making up a compound turn may leave the subgroup,
+    /// so every turn in the move set is applied to the raw state directly instead of being composed from
+    /// base turn tables.
+    pub fn new_subgroup_table<C: BasicCoordinate>(coord_type: C, move_set: &[Turn]) -> error::Result<Self> {
+        Self::new_subgroup_table_with_options(coord_type, move_set, &GenerationOptions::default())
+    }
+
+    /// Builds subgroup tables, reporting progress after the table of each turn and stopping if cancelled
+    pub fn new_subgroup_table_with_options<C: BasicCoordinate>(coord_type: C, move_set: &[Turn], options: &GenerationOptions) -> error::Result<Self> {
+        let start = Instant::now();
+        let size = coord_type.get_size();
+        let mut turn_tables = Vec::new();
+        for turn in move_set {
+            options.check_cancelled()?;
+            turn_tables.push((*turn, MoveTable::generate_from_base_turn(coord_type, turn)?));
+            options.report(turn_tables.len() * size, move_set.len() * size, None, start);
+        }
+        Ok(Self::from_turn_tables(turn_tables, move_set, size))
+    }
+
+    /// Combines the move tables of two coordinates into move tables for the coordinate
+    /// first * second_size + second. Both tables must have been built for the same turns.
+    pub fn new_product_table(first: &MoveTables, second: &MoveTables) -> error::Result<Self> {
+        let turns = first.get_turns().to_vec();
+        let second_indices = turns.iter()
+            .map(|turn| second.get_turn_index(turn).ok_or(Error::MissingMoveTable(*turn)))
+            .collect::<error::Result<Vec<usize>>>()?;
+        let second_size = second.get_size();
+        let size = first.get_size() * second_size;
+        let num_turns = turns.len();
+
+        let mut table = vec![0; size * num_turns];
+        if num_turns > 0 {
+            table.par_chunks_mut(num_turns).enumerate().for_each(|(coord, new_coords)| {
+                let (first_coord, second_coord) = (coord / second_size, coord % second_size);
+                for (index, second_index) in second_indices.iter().enumerate() {
+                    let new_coord = first.apply_move_index_to_coord(first_coord, index) * second_size
+                        + second.apply_move_index_to_coord(second_coord, *second_index);
+                    new_coords[index] = new_coord as u32;
+                }
+            });
+        }
+
+        Ok(Self {
+            table,
+            turns,
+            num_search_turns: num_turns,
+            size,
+        })
+    }
+
+    pub fn apply_move_index_to_coord(&self, coord: usize, turn_index: usize) -> usize {
+        self.table[coord * self.turns.len() + turn_index] as usize
+    }
+
+    pub fn apply_move_to_coord(&self, coord: usize, turn: &Turn) -> error::Result<usize> {
+        let turn_index = self.get_turn_index(turn)
+            .ok_or(Error::MissingMoveTable(*turn))?;
+        Ok(self.apply_move_index_to_coord(coord, turn_index))
+    }
+
+    // Applies a turn from its own table if it has one, or else from the tables of its base turns
+    fn apply_turn_or_base_turns(&self, coord: usize, turn: &Turn) -> error::Result<usize> {
+        if let Some(turn_index) = self.get_turn_index(turn) {
+            return Ok(self.apply_move_index_to_coord(coord, turn_index));
+        }
+        turn.to_base_turns().iter()
+            .try_fold(coord, |coord, base_turn| self.apply_move_to_coord(coord, base_turn))
+    }
+
+    /// Checks that every turn is a permutation of the coordinates, that every turn followed by its inverse
+    /// returns to where it started, and that every compound turn matches its base turns where those are
+    /// stored. Mistakes in turn effects or coordinate definitions show up here as the turn and coordinate
+    /// that went wrong, rather than as a search finding wrong solutions.
+    pub fn verify(&self) -> error::Result<()> {
+        for (turn_index, turn) in self.turns.iter().enumerate() {
+            let mut reached = vec![false; self.size];
+            for coord in 0..self.size {
+                let new_coord = self.apply_move_index_to_coord(coord, turn_index);
+                if reached[new_coord] {
+                    return Err(Error::NotAPermutation { turn: *turn, coord: new_coord });
+                }
+                reached[new_coord] = true;
+            }
+
+            let inverse = turn.invert();
+            (0..self.size).into_par_iter().try_for_each(|coord| {
+                let new_coord = self.apply_move_index_to_coord(coord, turn_index);
+                match self.apply_turn_or_base_turns(new_coord, &inverse)? == coord {
+                    true => Ok(()),
+                    false => Err(Error::InverseMismatch { turn: *turn, coord }),
+                }
+            })?;
+
+            // Subgroup and product tables only store the turns offered to a search
+            let base_turns = turn.to_base_turns();
+            if turn.is_base_move() || base_turns.iter().any(|base_turn| self.get_turn_index(base_turn).is_none()) {
+                continue;
+            }
+            (0..self.size).into_par_iter().try_for_each(|coord| {
+                let composed = base_turns.iter()
+                    .try_fold(coord, |coord, base_turn| self.apply_move_to_coord(coord, base_turn))?;
+                match composed == self.apply_move_index_to_coord(coord, turn_index) {
+                    true => Ok(()),
+                    false => Err(Error::CompositionMismatch { turn: *turn, coord }),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the number of turns offered to a search and every stored turn, followed by the flat table
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend((self.num_search_turns as u32).to_le_bytes());
+        bytes.extend((self.turns.len() as u32).to_le_bytes());
+        for turn in &self.turns {
+            bytes.extend(Into::<u32>::into(turn).to_le_bytes());
+        }
+        for coord in &self.table {
+            bytes.extend(coord.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Reads tables written by to_bytes for a coordinate with the given number of values
+    pub fn from_bytes(bytes: &[u8], size: usize) -> error::Result<Self> {
+        let mut reader = ByteReader::new(bytes);
+        let num_search_turns = reader.read_u32()? as usize;
+        let mut turns = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            turns.push(reader.read_u32()?.into());
+        }
+
+        let mut table = Vec::with_capacity(size * turns.len());
+        for _ in 0..size * turns.len() {
+            let coord = reader.read_u32()?;
+            if coord as usize >= size {
+                return Err(Error::InvalidTableData(format!("Coordinate {} out of range in move table", coord)));
             }
+            table.push(coord);
         }
+
+        if !reader.is_empty() || num_search_turns > turns.len() {
+            return Err(Error::InvalidTableData("Invalid move tables".to_string()));
+        }
+        Ok(Self {
+            table,
+            turns,
+            num_search_turns,
+            size,
+        })
     }
+}
+
 
-    pub fn new_basic_table<C: BasicCoordinate>(coord_type: C, move_set: &[Turn]) -> Self {
-        let mut tables = Self::empty();
-        tables.generate_base_tables(coord_type, move_set);
-        tables.generate_compound_tables(coord_type.get_size(), move_set);
-        tables
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::cp::CornerPermCoord;
+    use crate::coords::basic_coords::e_m_s_edges::ESliceEdgePermCoord;
+    use crate::coords::basic_coords::e_slice_edge_sep::ESliceEdgeSepCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::moveset::MoveSet;
+    use crate::rawcube::RawState;
+
+    #[test]
+    fn test_turn_indices() {
+        // R2 is built from R, which is stored but not offered to a search
+        let co = COUDCoord::new();
+        let move_set = MoveSet::dr_ud();
+        let move_tables = MoveTables::new_basic_table(co, &move_set).unwrap();
+        assert_eq!(MoveSet::new(move_tables.get_turns()), move_set);
+        assert!(move_tables.get_turn_index(&Turn::from_name("R")).unwrap() >= move_set.len());
+
+        let mut state = RawState::solved();
+        state.apply_algorithm(&Turn::get_vec_from_alg_string("R F' L B"));
+        let coord = co.convert_raw_state_to_coord(&state);
+        for (turn_index, turn) in move_tables.get_turns().iter().enumerate() {
+            assert_eq!(move_tables.get_turn_index(turn), Some(turn_index));
+            let mut new_state = state.clone();
+            new_state.apply_turn(turn);
+            let new_coord = co.convert_raw_state_to_coord(&new_state);
+            assert_eq!(move_tables.apply_move_index_to_coord(coord, turn_index), new_coord);
+            assert_eq!(move_tables.apply_move_to_coord(coord, turn).unwrap(), new_coord);
+        }
     }
 
-    pub fn apply_move_to_coord(&self, coord: usize, turn: &Turn) -> usize {
-        let table = self.table.get(&turn)
-        .expect("Move table not found for turn");
-        let new_coord = table.table[coord];
-        new_coord
+    #[test]
+    fn test_verify() {
+        let co = COUDCoord::new();
+        let eo = EOFBCoord::new();
+        let cp = CornerPermCoord::new();
+        let e_slice_edges = ESliceEdgePermCoord::new();
+        let e_slice = ESliceEdgeSepCoord::new();
+        MoveTables::new_basic_table(co, &MoveSet::full_with_slices()).unwrap().verify().unwrap();
+        MoveTables::new_basic_table(eo, &MoveSet::full_with_slices()).unwrap().verify().unwrap();
+        MoveTables::new_basic_table(cp, &MoveSet::full_with_slices()).unwrap().verify().unwrap();
+        MoveTables::new_basic_table(e_slice_edges, &MoveSet::full_with_slices()).unwrap().verify().unwrap();
+        let co_tables = MoveTables::new_basic_table(co, &MoveSet::dr_ud()).unwrap();
+        let e_slice_tables = MoveTables::new_basic_table(e_slice, &MoveSet::dr_ud()).unwrap();
+        MoveTables::new_product_table(&co_tables, &e_slice_tables).unwrap().verify().unwrap();
+
+        // Sending two coordinates to the same place is caught with the turn that does it
+        let mut move_tables = MoveTables::new_basic_table(co, &MoveSet::dr_ud()).unwrap();
+        let turn_index = move_tables.get_turn_index(&Turn::from_name("U")).unwrap();
+        move_tables.table[turn_index] = move_tables.table[move_tables.turns.len() + turn_index];
+        assert!(matches!(move_tables.verify(), Err(Error::NotAPermutation { .. })));
     }
 }
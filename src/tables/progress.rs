@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::error::{self, Error};
+
+/// How far the generation of a table has got
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    /// The number of entries filled so far, out of total
+    pub filled: usize,
+    pub total: usize,
+    /// The distance the last pass of a pruning table filled, or nothing for move tables
+    pub depth: Option<u8>,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Estimates the time left, assuming the remaining entries take as long as the ones filled so far
+    pub fn get_eta(&self) -> Option<Duration> {
+        if self.filled == 0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((self.total - self.filled) as f64 / self.filled as f64))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.filled == self.total
+    }
+}
+
+pub type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Stops table generation once cancelled. Clones share the same state, so generation can be cancelled from
+/// another thread. Generation stops at the next turn of a move table or pass of a pruning table.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Reports the progress of generating a table, and lets it be cancelled
+#[derive(Clone, Default)]
+pub struct GenerationOptions {
+    pub progress: Option<ProgressCallback>,
+    pub cancellation: CancellationToken,
+}
+
+impl GenerationOptions {
+    pub fn new(progress: Option<ProgressCallback>, cancellation: CancellationToken) -> Self {
+        Self {
+            progress,
+            cancellation,
+        }
+    }
+
+    pub fn report(&self, filled: usize, total: usize, depth: Option<u8>, start: Instant) {
+        if let Some(progress) = &self.progress {
+            progress(&Progress { filled, total, depth, elapsed: start.elapsed() });
+        }
+    }
+
+    pub fn check_cancelled(&self) -> error::Result<()> {
+        match self.cancellation.is_cancelled() {
+            true => Err(Error::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::coords::Coordinate;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::tables::movetables::MoveTables;
+    use crate::tables::pruningtables::{PruningTable, PruningEncoding, GoalSet};
+
+    #[test]
+    fn test_progress_and_cancellation() {
+        let eo = EOFBCoord::new();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress_reports = reports.clone();
+        let options = GenerationOptions::new(
+            Some(Arc::new(move |progress: &Progress| progress_reports.lock().unwrap().push(progress.clone()))),
+            CancellationToken::new(),
+        );
+
+        // Move tables report once per turn, and pruning tables once per distance
+        let move_tables = MoveTables::new_basic_table_with_options(eo, &eo.get_allowed_turns(), &options).unwrap();
+        let move_reports = reports.lock().unwrap().drain(..).collect::<Vec<_>>();
+        assert_eq!(move_reports.len(), eo.get_allowed_turns().len());
+        assert!(move_reports.iter().all(|progress| progress.depth.is_none()));
+        assert!(move_reports.last().unwrap().is_finished());
+
+        let pruning_table = PruningTable::new_with_options(eo, &move_tables, PruningEncoding::Byte, &GoalSet::solved(eo), &options).unwrap();
+        let pruning_reports = reports.lock().unwrap().drain(..).collect::<Vec<_>>();
+        assert!(pruning_reports.windows(2).all(|pair| pair[0].filled < pair[1].filled));
+        let last = pruning_reports.last().unwrap();
+        assert!(last.is_finished());
+        assert_eq!(last.total, eo.get_size());
+        assert_eq!(last.get_eta(), Some(Duration::ZERO));
+        assert_eq!(last.depth, Some(pruning_table.get_stats(&move_tables).unwrap().max_distance));
+
+        // Once cancelled, nothing more is generated
+        options.cancellation.clone().cancel();
+        assert!(matches!(MoveTables::new_basic_table_with_options(eo, &eo.get_allowed_turns(), &options), Err(Error::Cancelled)));
+        assert!(matches!(PruningTable::new_with_options(eo, &move_tables, PruningEncoding::Byte, &GoalSet::solved(eo), &options), Err(Error::Cancelled)));
+        assert!(reports.lock().unwrap().is_empty());
+    }
+}
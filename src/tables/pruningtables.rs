@@ -1,98 +1,668 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, Range};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::coords::Coordinate;
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error::{self, Error};
+use crate::rawcube::RawState;
 use crate::turndef::Turn;
 use crate::tables::movetables::MoveTables;
+use crate::tables::cache::ByteReader;
+use crate::tables::progress::GenerationOptions;
 
 const MAX_SEARCH_DISTANCE: u8 = 25;
 
-/// PruningTable stores the distance of each coordinate from the solved state
+/// How the distances of a pruning table are stored.
+/// Byte stores one distance per byte.
+/// Nibble packs two distances into each byte, for tables where no distance is above 15.
+/// Mod3 packs four distances modulo 3 into each byte. A distance modulo 3 is enough during a search, as a turn
+/// changes the distance by at most 1, so the exact distance follows from the distance before the turn. The
+/// distance of the first coordinate of a search is found by following turns that lower the distance to a solved
+/// coordinate, which needs the move tables the pruning table was built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PruningEncoding {
+    Byte,
+    Nibble,
+    Mod3,
+}
+
+impl PruningEncoding {
+    /// The number of bytes needed for a table of size coordinates
+    pub fn get_byte_length(&self, size: usize) -> usize {
+        match self {
+            PruningEncoding::Byte => size,
+            PruningEncoding::Nibble => size.div_ceil(2),
+            PruningEncoding::Mod3 => size.div_ceil(4),
+        }
+    }
+
+    fn encode(&self, distances: &[u8]) -> error::Result<Vec<u8>> {
+        let (bits, max_value) = match self {
+            PruningEncoding::Byte => return Ok(distances.to_vec()),
+            PruningEncoding::Nibble => (4, 15),
+            PruningEncoding::Mod3 => (2, 3),
+        };
+        let per_byte = 8 / bits;
+        let mut table = vec![0; self.get_byte_length(distances.len())];
+        for (coord, distance) in distances.iter().enumerate() {
+            let value = match self {
+                PruningEncoding::Mod3 => distance % 3,
+                _ => *distance,
+            };
+            if value > max_value {
+                return Err(Error::UnencodableDistance { distance: *distance, encoding: *self });
+            }
+            table[coord / per_byte] |= value << (coord % per_byte * bits);
+        }
+        Ok(table)
+    }
+
+    // Returns the stored value, which is the distance modulo 3 for Mod3 tables
+    fn decode(&self, table: &[u8], coord: usize) -> u8 {
+        match self {
+            PruningEncoding::Byte => table[coord],
+            PruningEncoding::Nibble => (table[coord / 2] >> (coord % 2 * 4)) & 0xF,
+            PruningEncoding::Mod3 => (table[coord / 4] >> (coord % 4 * 2)) & 0x3,
+        }
+    }
+}
+
+/// The coordinates a pruning table measures distances to. Usually these are the solved coordinates of the
+/// coordinate, but a step that accepts several end states, such as a block built anywhere on the cube or any
+/// DR-compatible slice state, can use a single table for the distance to the nearest of them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GoalSet {
+    coords: Vec<usize>,
+}
+
+impl GoalSet {
+    pub fn from_coords(coords: &[usize]) -> Self {
+        let mut coords = coords.to_vec();
+        coords.sort();
+        coords.dedup();
+        Self {
+            coords,
+        }
+    }
+
+    pub fn solved<C: Coordinate>(coord_type: C) -> Self {
+        Self::from_coords(&coord_type.get_solved_coords())
+    }
+
+    /// Every coordinate the predicate accepts
+    pub fn from_predicate<C: Coordinate>(coord_type: C, predicate: impl Fn(usize) -> bool + Sync) -> Self {
+        Self {
+            coords: (0..coord_type.get_size()).into_par_iter().filter(|coord| predicate(*coord)).collect(),
+        }
+    }
+
+    /// Every coordinate whose example state the predicate accepts. The predicate must only depend on
+    /// the pieces the coordinate describes, as the rest of an example state is arbitrary.
+    pub fn from_raw_state_predicate<C: BasicCoordinate>(coord_type: C, predicate: impl Fn(&RawState) -> bool + Sync) -> Self {
+        Self::from_predicate(coord_type, |coord| predicate(&coord_type.convert_coord_to_example_raw_state(coord)))
+    }
+
+    /// The coordinates in either goal set
+    pub fn union(&self, other: &GoalSet) -> Self {
+        let mut coords = self.coords.clone();
+        coords.extend(&other.coords);
+        Self::from_coords(&coords)
+    }
+
+    /// The goal coordinates, sorted
+    pub fn get_coords(&self) -> &[usize] {
+        &self.coords
+    }
+}
+
+/// The distances of a pruning table, either generated in memory, mapped read-only from a cached file
+/// or embedded in the binary
+enum TableData {
+    Owned(Vec<u8>),
+    Mapped(Mmap, Range<usize>),
+    Static(&'static [u8]),
+}
+
+impl Deref for TableData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            TableData::Owned(table) => table,
+            TableData::Mapped(map, range) => &map[range.clone()],
+            TableData::Static(table) => table,
+        }
+    }
+}
+
+/// PruningTable stores the distance of each coordinate from the solved state, or from the nearest coordinate
+/// of its goal set.
+/// The turn set is the turns of the move tables it was built with, in the same order, so turn
+/// indices of those move tables can be used with it directly.
 pub struct PruningTable {
-    table: Vec<u8>,
+    table: TableData,
+    encoding: PruningEncoding,
     turn_set: Vec<Turn>,
     num_filled: usize,
     max_size: usize,
-    solved_coords: Vec<usize>,
+    goal_coords: Vec<usize>,
+    generation_time: Option<Duration>,
+    pass_fills: Vec<usize>,
+}
+
+/// Statistics of a pruning table, for checking tables against known distance distributions
+#[derive(Clone, Debug, PartialEq)]
+pub struct PruningTableStats {
+    /// The number of coordinates at each distance
+    pub histogram: Vec<usize>,
+    pub max_distance: u8,
+    pub average_distance: f64,
+    /// The number of coordinates filled by each pass of generation, or nothing if the table was loaded from a file
+    pub pass_fills: Vec<usize>,
+    pub generation_time: Option<Duration>,
+}
+
+impl fmt::Display for PruningTableStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let size: usize = self.histogram.iter().sum();
+        writeln!(f, "Distance  Coordinates")?;
+        for (distance, count) in self.histogram.iter().enumerate() {
+            writeln!(f, "{:>8}  {:>11}  {:>6.2}%", distance, count, *count as f64 * 100.0 / size as f64)?;
+        }
+        write!(f, "Max distance: {}, average distance: {:.3}", self.max_distance, self.average_distance)?;
+        if !self.pass_fills.is_empty() {
+            let pass_fills: Vec<String> = self.pass_fills.iter().map(|count| count.to_string()).collect();
+            write!(f, "\nFilled per pass: {}", pass_fills.join(", "))?;
+        }
+        if let Some(time) = self.generation_time {
+            write!(f, "\nGenerated in {} seconds", time.as_micros() as f64 / 1_000_000.0)?;
+        }
+        Ok(())
+    }
 }
 
 impl PruningTable {
-    fn empty<C: Coordinate>(coord_type: C, turn_sets: &[Turn]) -> Self {
+    fn empty<C: Coordinate>(coord_type: C, turn_sets: &[Turn], goals: &GoalSet) -> Self {
         Self {
-            table: Vec::new(),
+            table: TableData::Owned(Vec::new()),
+            encoding: PruningEncoding::Byte,
             turn_set: turn_sets.to_vec(),
             num_filled: 0,
             max_size: coord_type.get_size(),
-            solved_coords: coord_type.get_solved_coords(),
+            goal_coords: goals.get_coords().to_vec(),
+            generation_time: None,
+            pass_fills: Vec::new(),
         }
     }
 
-    fn init_table(&mut self) {
-        self.table = vec![u8::MAX; self.max_size];
-        for solved_coord in &self.solved_coords {
-            self.table[*solved_coord] = 0;
+    fn init_table(&mut self) -> Vec<AtomicU8> {
+        let table: Vec<AtomicU8> = (0..self.max_size).map(|_| AtomicU8::new(u8::MAX)).collect();
+        for goal_coord in &self.goal_coords {
+            table[*goal_coord].store(0, Ordering::Relaxed);
             self.num_filled += 1;
         }
+        table
     }
 
-    fn forward_fill_single_pass(&mut self, distance: u8, movetables: &MoveTables) {
-        for coord in 0..self.max_size {
-            if self.table[coord] == distance {
-                for turn in &self.turn_set {
-                    let new_coord = movetables.apply_move_to_coord(coord, turn);
-                    if self.table[new_coord] == u8::MAX {
-                        self.table[new_coord] = distance + 1;
-                        self.num_filled += 1;
+    // Each pass only writes distance + 1 and only reads distance or unfilled entries, so the
+    // order coordinates are visited in does not change the result, and passes can run in parallel.
+    fn forward_fill_single_pass(&self, table: &[AtomicU8], distance: u8, movetables: &MoveTables) -> usize {
+        (0..self.max_size).into_par_iter().map(|coord| {
+            let mut num_filled = 0;
+            if table[coord].load(Ordering::Relaxed) == distance {
+                for turn_index in 0..self.turn_set.len() {
+                    let new_coord = movetables.apply_move_index_to_coord(coord, turn_index);
+                    if table[new_coord].compare_exchange(u8::MAX, distance + 1, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                        num_filled += 1;
                     }
                 }
             }
-        }
+            num_filled
+        }).sum()
     }
 
-    fn backward_fill_single_pass(&mut self, distance: u8, movetables: &MoveTables) {
-        for coord in 0..self.max_size {
-            if self.table[coord] > distance {
-                for turn in &self.turn_set {
-                    let new_coord = movetables.apply_move_to_coord(coord, turn);
-                    if self.table[new_coord] == distance {
-                        self.table[coord] = distance + 1;
-                        self.num_filled += 1;
-                        break;
-                    }
+    fn backward_fill_single_pass(&self, table: &[AtomicU8], distance: u8, movetables: &MoveTables) -> usize {
+        (0..self.max_size).into_par_iter().filter(|coord| {
+            if table[*coord].load(Ordering::Relaxed) <= distance {
+                return false;
+            }
+            for turn_index in 0..self.turn_set.len() {
+                let new_coord = movetables.apply_move_index_to_coord(*coord, turn_index);
+                if table[new_coord].load(Ordering::Relaxed) == distance {
+                    table[*coord].store(distance + 1, Ordering::Relaxed);
+                    return true;
                 }
             }
-        }
+            false
+        }).count()
     }
 
-    fn generate(&mut self, move_tables: &MoveTables) {
-        self.init_table();
+    fn generate(&mut self, move_tables: &MoveTables, options: &GenerationOptions) -> error::Result<()> {
+        let start = Instant::now();
+        let table = self.init_table();
         let mut distance = 0;
         let size = self.max_size;
         let forward_threshold = size / 2;
         while self.num_filled < size {
-            if self.num_filled < forward_threshold {
-                self.forward_fill_single_pass(distance, move_tables);
+            options.check_cancelled()?;
+            let num_filled = if self.num_filled < forward_threshold {
+                self.forward_fill_single_pass(&table, distance, move_tables)
             } else {
-                self.backward_fill_single_pass(distance, move_tables);
-            }
+                self.backward_fill_single_pass(&table, distance, move_tables)
+            };
+            self.num_filled += num_filled;
+            self.pass_fills.push(num_filled);
             distance += 1;
+            options.report(self.num_filled, size, Some(distance), start);
 
             if distance > MAX_SEARCH_DISTANCE {
-                panic!("Pruning table generation failed. Not all coordinates were filled. ({})", self.num_filled);
+                return Err(Error::UnfilledPruningTable { num_filled: self.num_filled, size });
             }
         }
+        self.table = TableData::Owned(table.into_iter().map(AtomicU8::into_inner).collect());
+        self.generation_time = Some(start.elapsed());
+        Ok(())
+    }
+
+    pub fn new<C: Coordinate>(coord_type: C, move_tables: &MoveTables) -> error::Result<Self> {
+        Self::new_with_encoding(coord_type, move_tables, PruningEncoding::Byte)
+    }
+
+    pub fn new_with_encoding<C: Coordinate>(coord_type: C, move_tables: &MoveTables, encoding: PruningEncoding) -> error::Result<Self> {
+        Self::new_with_goals(coord_type, move_tables, encoding, &GoalSet::solved(coord_type))
     }
 
-    pub fn new<C: Coordinate>(coord_type: C, move_tables: &MoveTables) -> Self {
+    /// Builds a table of the distance of each coordinate to the nearest coordinate of the goal set,
+    /// rather than to the solved coordinates
+    pub fn new_with_goals<C: Coordinate>(coord_type: C, move_tables: &MoveTables, encoding: PruningEncoding, goals: &GoalSet) -> error::Result<Self> {
+        Self::new_with_options(coord_type, move_tables, encoding, goals, &GenerationOptions::default())
+    }
+
+    /// Builds a table towards the goal set, reporting progress after each pass and stopping if cancelled
+    pub fn new_with_options<C: Coordinate>(coord_type: C, move_tables: &MoveTables, encoding: PruningEncoding, goals: &GoalSet,
+            options: &GenerationOptions) -> error::Result<Self> {
+        if let Some(coord) = goals.get_coords().iter().find(|coord| **coord >= coord_type.get_size()) {
+            return Err(Error::InvalidGoal { coord: *coord, size: coord_type.get_size() });
+        }
         let mut tables = Self::empty(
             coord_type,
             move_tables.get_turns(),
+            goals,
         );
-        tables.generate(move_tables);
-        tables
+        tables.generate(move_tables, options)?;
+        if encoding != PruningEncoding::Byte {
+            tables.table = TableData::Owned(encoding.encode(&tables.table)?);
+            tables.encoding = encoding;
+        }
+        Ok(tables)
+    }
+
+    /// Keeps the generation time and pass fills of the table this one was loaded from after generating it
+    pub fn copy_generation_details(&mut self, generated: &PruningTable) {
+        self.generation_time = generated.generation_time;
+        self.pass_fills = generated.pass_fills.clone();
+    }
+
+    /// Counts the coordinates at each distance. The move tables are only needed for Mod3 tables.
+    pub fn get_stats(&self, move_tables: &MoveTables) -> error::Result<PruningTableStats> {
+        let histogram = (0..self.max_size).into_par_iter()
+            .try_fold(Vec::new, |mut histogram: Vec<usize>, coord| {
+                let distance = self.get_exact_distance(coord, move_tables)? as usize;
+                if histogram.len() <= distance {
+                    histogram.resize(distance + 1, 0);
+                }
+                histogram[distance] += 1;
+                Ok(histogram)
+            })
+            .try_reduce(Vec::new, |mut first, second| {
+                if first.len() < second.len() {
+                    first.resize(second.len(), 0);
+                }
+                for (distance, count) in second.into_iter().enumerate() {
+                    first[distance] += count;
+                }
+                Ok(first)
+            })?;
+
+        let total: usize = histogram.iter().enumerate().map(|(distance, count)| distance * count).sum();
+        Ok(PruningTableStats {
+            max_distance: histogram.len().saturating_sub(1) as u8,
+            average_distance: total as f64 / self.max_size as f64,
+            histogram,
+            pass_fills: self.pass_fills.clone(),
+            generation_time: self.generation_time,
+        })
+    }
+
+    /// Returns the distance of a coordinate from the solved state. Mod3 tables can not find a distance
+    /// on their own and give an error, so get_exact_distance must be used for them instead.
+    pub fn get_distance(&self, coord: usize) -> error::Result<u8> {
+        if self.encoding == PruningEncoding::Mod3 {
+            return Err(Error::MissingMoveTables);
+        }
+        Ok(self.encoding.decode(&self.table, coord))
+    }
+
+    /// Returns the distance of a coordinate from the solved state for any encoding, using the move tables
+    /// the table was built with. For Mod3 tables this follows a path to the solved state, so searches should
+    /// only use it for their first coordinate and use get_distance_after_turn after that.
+    pub fn get_exact_distance(&self, coord: usize, move_tables: &MoveTables) -> error::Result<u8> {
+        if self.encoding != PruningEncoding::Mod3 {
+            return self.get_distance(coord);
+        }
+
+        if move_tables.get_size() != self.max_size {
+            return Err(Error::InconsistentDistance { coord });
+        }
+
+        // Every unsolved coordinate has a neighbour one turn closer, which is the only neighbour whose
+        // distance is one less modulo 3, since neighbours are at most one turn further or closer
+        let mut current = coord;
+        let mut distance = 0;
+        while self.goal_coords.binary_search(&current).is_err() {
+            let closer_value = (self.encoding.decode(&self.table, current) + 2) % 3;
+            current = (0..move_tables.get_turns().len())
+                .map(|turn_index| move_tables.apply_move_index_to_coord(current, turn_index))
+                .find(|new_coord| self.encoding.decode(&self.table, *new_coord) == closer_value)
+                .ok_or(Error::InconsistentDistance { coord: current })?;
+            distance += 1;
+            // A path longer than any distance a byte can hold must be going around in circles
+            if distance == u8::MAX {
+                return Err(Error::InconsistentDistance { coord });
+            }
+        }
+        Ok(distance)
+    }
+
+    /// Checks that only goal coordinates have distance 0, that no turn changes a distance by more than one
+    /// and that every unsolved coordinate has a neighbour one turn closer. Mod3 tables only store distances
+    /// modulo 3, so for them a turn can only be checked to change the stored value by at most one.
+    /// This assumes the move set holds the inverse of each of its turns, as every move set of a step does.
+    pub fn verify(&self, move_tables: &MoveTables) -> error::Result<()> {
+        let mut is_goal = vec![false; self.max_size];
+        for coord in &self.goal_coords {
+            is_goal[*coord] = true;
+        }
+
+        let turns = move_tables.get_turns();
+        (0..self.max_size).into_par_iter().try_for_each(|coord| {
+            let value = self.encoding.decode(&self.table, coord);
+            let invalid_value = match self.encoding {
+                PruningEncoding::Mod3 => value > 2 || (is_goal[coord] && value != 0),
+                _ => is_goal[coord] != (value == 0),
+            };
+            if invalid_value {
+                return Err(Error::InconsistentDistance { coord });
+            }
+
+            let mut has_closer_neighbour = false;
+            for (turn_index, turn) in turns.iter().enumerate() {
+                let new_value = self.encoding.decode(&self.table, move_tables.apply_move_index_to_coord(coord, turn_index));
+                let change = match self.encoding {
+                    PruningEncoding::Mod3 if new_value <= 2 => (new_value as i16 + 4 - value as i16) % 3 - 1,
+                    _ => new_value as i16 - value as i16,
+                };
+                if change.abs() > 1 {
+                    return Err(Error::DistanceJump { turn: *turn, coord });
+                }
+                has_closer_neighbour |= change == -1;
+            }
+            match is_goal[coord] || has_closer_neighbour {
+                true => Ok(()),
+                false => Err(Error::InconsistentDistance { coord }),
+            }
+        })
+    }
+
+    /// Returns the distance of a coordinate reached by one turn from a coordinate at previous_distance
+    pub fn get_distance_after_turn(&self, coord: usize, previous_distance: u8) -> u8 {
+        let value = self.encoding.decode(&self.table, coord);
+        if self.encoding != PruningEncoding::Mod3 {
+            return value;
+        }
+        [previous_distance + 1, previous_distance, previous_distance.wrapping_sub(1)].into_iter()
+            .find(|distance| distance % 3 == value)
+            .unwrap()
+    }
+
+    /// Serializes the goal coordinates followed by the distance of every coordinate
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend((self.goal_coords.len() as u32).to_le_bytes());
+        for coord in &self.goal_coords {
+            bytes.extend((*coord as u64).to_le_bytes());
+        }
+        bytes.extend(self.table.iter());
+        bytes
+    }
+
+    /// Reads a table written by to_bytes for a coordinate with the given number of values
+    pub fn from_bytes(bytes: &[u8], size: usize, turn_set: &[Turn], encoding: PruningEncoding) -> error::Result<Self> {
+        let (goal_coords, distances) = Self::parse_bytes(bytes, size, encoding)?;
+        let table = TableData::Owned(bytes[distances].to_vec());
+        Ok(Self::loaded(table, encoding, size, goal_coords, turn_set))
+    }
+
+    /// Uses a table written by to_bytes in place within a memory-mapped file, without copying the distances
+    pub fn from_mapped(map: Mmap, payload: Range<usize>, size: usize, turn_set: &[Turn], encoding: PruningEncoding) -> error::Result<Self> {
+        let (goal_coords, distances) = Self::parse_bytes(&map[payload.clone()], size, encoding)?;
+        let table = TableData::Mapped(map, payload.start + distances.start..payload.start + distances.end);
+        Ok(Self::loaded(table, encoding, size, goal_coords, turn_set))
+    }
+
+    /// Uses a table written by to_bytes in place within static bytes, such as tables embedded in the binary
+    pub fn from_static(bytes: &'static [u8], size: usize, turn_set: &[Turn], encoding: PruningEncoding) -> error::Result<Self> {
+        let (goal_coords, distances) = Self::parse_bytes(bytes, size, encoding)?;
+        let table = TableData::Static(&bytes[distances]);
+        Ok(Self::loaded(table, encoding, size, goal_coords, turn_set))
+    }
+
+    /// The coordinates the table measures distances to, sorted
+    pub fn get_goal_coords(&self) -> &[usize] {
+        &self.goal_coords
+    }
+
+    pub fn is_memory_mapped(&self) -> bool {
+        matches!(self.table, TableData::Mapped(..))
+    }
+
+    // Returns the sorted goal coordinates and where the distances are in the bytes
+    fn parse_bytes(bytes: &[u8], size: usize, encoding: PruningEncoding) -> error::Result<(Vec<usize>, Range<usize>)> {
+        let mut reader = ByteReader::new(bytes);
+        let mut goal_coords = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let coord = reader.read_u64()? as usize;
+            if coord >= size {
+                return Err(Error::InvalidTableData(format!("Goal coordinate {} out of range in pruning table", coord)));
+            }
+            goal_coords.push(coord);
+        }
+        goal_coords.sort();
+        let start = reader.get_position();
+        let length = encoding.get_byte_length(size);
+        let table = reader.read_bytes(length)?;
+        let is_valid = encoding != PruningEncoding::Byte || table.iter().all(|distance| *distance <= MAX_SEARCH_DISTANCE);
+        if !reader.is_empty() || !is_valid {
+            return Err(Error::InvalidTableData("Invalid pruning table".to_string()));
+        }
+        Ok((goal_coords, start..start + length))
+    }
+
+    fn loaded(table: TableData, encoding: PruningEncoding, size: usize, goal_coords: Vec<usize>, turn_set: &[Turn]) -> Self {
+        Self {
+            max_size: size,
+            num_filled: size,
+            table,
+            encoding,
+            turn_set: turn_set.to_vec(),
+            goal_coords,
+            generation_time: None,
+            pass_fills: Vec::new(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::moveset::MoveSet;
+    use crate::turndef::Turn;
+
+    // Plain breadth first search from the goal coordinates
+    fn get_distances<C: Coordinate>(coord_type: C, move_tables: &MoveTables, goals: &GoalSet) -> Vec<u8> {
+        let mut distances = vec![u8::MAX; coord_type.get_size()];
+        let mut queue = VecDeque::new();
+        for &coord in goals.get_coords() {
+            distances[coord] = 0;
+            queue.push_back(coord);
+        }
+        while let Some(coord) = queue.pop_front() {
+            for turn in move_tables.get_turns() {
+                let new_coord = move_tables.apply_move_to_coord(coord, turn).unwrap();
+                if distances[new_coord] == u8::MAX {
+                    distances[new_coord] = distances[coord] + 1;
+                    queue.push_back(new_coord);
+                }
+            }
+        }
+        distances
+    }
+
+    #[test]
+    fn test_parallel_generation() {
+        let co = COUDCoord::new();
+        let eo = EOFBCoord::new();
+        let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let co_table = PruningTable::new(co, &co_move_tables).unwrap();
+        let eo_table = PruningTable::new(eo, &eo_move_tables).unwrap();
+
+        for (coord, distance) in get_distances(co, &co_move_tables, &GoalSet::solved(co)).into_iter().enumerate() {
+            assert_eq!(co_table.get_distance(coord).unwrap(), distance);
+        }
+        for (coord, distance) in get_distances(eo, &eo_move_tables, &GoalSet::solved(eo)).into_iter().enumerate() {
+            assert_eq!(eo_table.get_distance(coord).unwrap(), distance);
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let co = COUDCoord::new();
+        let eo = EOFBCoord::new();
+        let co_move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let eo_move_tables = MoveTables::new_basic_table(eo, &eo.get_allowed_turns()).unwrap();
+        let co_table = PruningTable::new(co, &co_move_tables).unwrap();
+        let eo_table = PruningTable::new(eo, &eo_move_tables).unwrap();
+
+        // Known distributions of corner and edge orientation in the half turn metric
+        let co_stats = co_table.get_stats(&co_move_tables).unwrap();
+        assert_eq!(co_stats.histogram, vec![1, 4, 34, 186, 816, 1018, 128]);
+        assert_eq!(co_stats.max_distance, 6);
+        assert_eq!(co_stats.pass_fills, co_stats.histogram[1..].to_vec());
+        assert!(co_stats.generation_time.is_some());
+        let eo_stats = eo_table.get_stats(&eo_move_tables).unwrap();
+        assert_eq!(eo_stats.histogram, vec![1, 2, 25, 202, 620, 900, 285, 13]);
+        assert!((eo_stats.average_distance - 9439.0 / 2048.0).abs() < 1e-9);
+
+        let mod3_table = PruningTable::new_with_encoding(co, &co_move_tables, PruningEncoding::Mod3).unwrap();
+        assert_eq!(mod3_table.get_stats(&co_move_tables).unwrap().histogram, co_stats.histogram);
+        let loaded = PruningTable::from_bytes(&co_table.to_bytes(), co.get_size(), co_move_tables.get_turns(), PruningEncoding::Byte).unwrap();
+        let loaded_stats = loaded.get_stats(&co_move_tables).unwrap();
+        assert_eq!(loaded_stats.histogram, co_stats.histogram);
+        assert!(loaded_stats.pass_fills.is_empty());
+        assert!(loaded_stats.to_string().starts_with("Distance  Coordinates\n       0            1"));
+    }
+
+    #[test]
+    fn test_encodings() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let byte_table = PruningTable::new(co, &move_tables).unwrap();
+        let nibble_table = PruningTable::new_with_encoding(co, &move_tables, PruningEncoding::Nibble).unwrap();
+        let mod3_table = PruningTable::new_with_encoding(co, &move_tables, PruningEncoding::Mod3).unwrap();
+        assert_eq!(nibble_table.to_bytes().len() - 12, co.get_size().div_ceil(2));
+        assert_eq!(mod3_table.to_bytes().len() - 12, co.get_size().div_ceil(4));
+
+        for coord in 0..co.get_size() {
+            let distance = byte_table.get_distance(coord).unwrap();
+            assert_eq!(nibble_table.get_distance(coord).unwrap(), distance);
+            assert_eq!(mod3_table.get_exact_distance(coord, &move_tables).unwrap(), distance);
+            for turn in move_tables.get_turns() {
+                let new_coord = move_tables.apply_move_to_coord(coord, turn).unwrap();
+                assert_eq!(mod3_table.get_distance_after_turn(new_coord, distance), byte_table.get_distance(new_coord).unwrap());
+            }
+        }
+
+        // Mod3 tables only give distances with the move tables they were built with
+        assert_eq!(mod3_table.get_distance(1), Err(Error::MissingMoveTables));
+        let eo_move_tables = MoveTables::new_basic_table(EOFBCoord::new(), &co.get_allowed_turns()).unwrap();
+        assert_eq!(mod3_table.get_exact_distance(1, &eo_move_tables), Err(Error::InconsistentDistance { coord: 1 }));
     }
 
-    pub fn get_distance(&self, coord: usize) -> u8 {
-        self.table[coord]
+    #[test]
+    fn test_errors() {
+        // Turns that keep edges oriented can never reach the other EO coordinates
+        let eo = EOFBCoord::new();
+        let move_tables = MoveTables::new_basic_table(eo, &MoveSet::eo_fb()).unwrap();
+        assert_eq!(PruningTable::new(eo, &move_tables).err(), Some(Error::UnfilledPruningTable { num_filled: 1, size: eo.get_size() }));
+        assert_eq!(move_tables.apply_move_to_coord(0, &Turn::from_name("M")), Err(Error::MissingMoveTable(Turn::from_name("M"))));
+        assert_eq!(EOFBCoord::new().apply_turn(0, &Turn::from_name("R2")), Err(Error::CompoundTurn(Turn::from_name("R2"))));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_verify() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        for encoding in [PruningEncoding::Byte, PruningEncoding::Nibble, PruningEncoding::Mod3] {
+            PruningTable::new_with_encoding(co, &move_tables, encoding).unwrap().verify(&move_tables).unwrap();
+        }
+
+        let mut pruning_table = PruningTable::new(co, &move_tables).unwrap();
+        let coord = move_tables.apply_move_to_coord(0, &Turn::from_name("R")).unwrap();
+        if let TableData::Owned(table) = &mut pruning_table.table {
+            table[coord] = 3;
+        }
+        assert!(matches!(pruning_table.verify(&move_tables), Err(Error::DistanceJump { .. })));
+    }
+
+    #[test]
+    fn test_goal_sets() {
+        let co = COUDCoord::new();
+        let move_tables = MoveTables::new_basic_table(co, &co.get_allowed_turns()).unwrap();
+        let solved = GoalSet::solved(co);
+        assert_eq!(solved, GoalSet::from_coords(&[0, 0]));
+        assert_eq!(GoalSet::from_raw_state_predicate(co, |state| co.convert_raw_state_to_coord(state) == 0), solved);
+
+        // Both the solved corners and the corners after R are goals
+        let after_r = move_tables.apply_move_to_coord(0, &Turn::from_name("R")).unwrap();
+        let goals = solved.union(&GoalSet::from_coords(&[after_r, after_r]));
+        assert_eq!(goals.get_coords(), [0, after_r]);
+        let table = PruningTable::new_with_goals(co, &move_tables, PruningEncoding::Mod3, &goals).unwrap();
+        table.verify(&move_tables).unwrap();
+        for (coord, distance) in get_distances(co, &move_tables, &goals).into_iter().enumerate() {
+            assert_eq!(table.get_exact_distance(coord, &move_tables).unwrap(), distance);
+        }
+
+        let goals = GoalSet::from_predicate(co, |coord| coord % 3 == 0);
+        assert_eq!(goals, GoalSet::from_coords(&(0..co.get_size()).step_by(3).collect::<Vec<usize>>()));
+        let table = PruningTable::new_with_goals(co, &move_tables, PruningEncoding::Byte, &goals).unwrap();
+        for (coord, distance) in get_distances(co, &move_tables, &goals).into_iter().enumerate() {
+            assert_eq!(table.get_distance(coord).unwrap(), distance);
+        }
+        let loaded = PruningTable::from_bytes(&table.to_bytes(), co.get_size(), move_tables.get_turns(), PruningEncoding::Byte).unwrap();
+        assert_eq!(loaded.goal_coords, goals.get_coords());
+
+        let out_of_range = GoalSet::from_coords(&[co.get_size()]);
+        assert_eq!(PruningTable::new_with_goals(co, &move_tables, PruningEncoding::Byte, &out_of_range).err(),
+            Some(Error::InvalidGoal { coord: co.get_size(), size: co.get_size() }));
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use lazy_static::lazy_static;
+
+use crate::coords::{Coordinate, BasicCoordinate};
+use crate::error;
+use crate::moveset::MoveSet;
+use crate::tables::cache::{self, TableHeader, TableKind};
+use crate::tables::embedded;
+use crate::tables::movetables::MoveTables;
+use crate::tables::progress::{CancellationToken, GenerationOptions, Progress};
+use crate::tables::pruningtables::{PruningTable, PruningEncoding, GoalSet};
+use crate::turndef::Turn;
+
+/*
+TableRegistry hands out tables by coordinate and move set, building each one the first time it is asked for
+and sharing it through an Arc after that, so steps that need the same tables do not build or hold them twice.
+Tables are keyed by the same identity, move set and kind that name them in the disk cache, along with the goals
+of pruning tables, so tables of one coordinate towards different goals are kept apart. Tables embedded
+in the binary are used first, then the disk cache if the registry has a cache directory, and otherwise tables
+are generated. A table that can not be saved to the cache is still used, and the error goes to a callback so
+that the program decides whether to show it.
+
+Each table has its own slot, so building one table only blocks threads asking for that same table, and a
+table that fails to build is tried again the next time it is asked for. Progress of every table the registry
+generates goes to one callback, given the identity of the table. Cancelling the token given to the registry
+stops every table it is generating, and cancelled tables are neither shared nor saved to the cache.
+*/
+
+lazy_static! {
+    // Tests build their tables in memory, so they never read or fill the cache of the user
+    static ref GLOBAL_REGISTRY: TableRegistry = TableRegistry::new((!cfg!(test)).then(cache::get_cache_dir));
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct TableKey {
+    kind: TableKind,
+    identity: String,
+    move_set: MoveSet,
+    goals: Option<GoalSet>,
+}
+
+impl TableKey {
+    fn new(kind: TableKind, identity: &str, move_set: &[Turn], goals: Option<&GoalSet>) -> Self {
+        Self {
+            kind,
+            identity: identity.to_string(),
+            move_set: MoveSet::new(move_set),
+            goals: goals.cloned(),
+        }
+    }
+}
+
+type Slots<T> = Mutex<HashMap<TableKey, Arc<Mutex<Option<Arc<T>>>>>>;
+
+pub type NamedProgressCallback = Arc<dyn Fn(&str, &Progress) + Send + Sync>;
+
+pub type CacheErrorCallback = Arc<dyn Fn(&Path, &io::Error) + Send + Sync>;
+
+/// Builds tables on first use and shares them between every caller and thread
+pub struct TableRegistry {
+    cache_dir: Option<PathBuf>,
+    progress: RwLock<Option<NamedProgressCallback>>,
+    cache_error: RwLock<Option<CacheErrorCallback>>,
+    cancellation: RwLock<CancellationToken>,
+    move_tables: Slots<MoveTables>,
+    pruning_tables: Slots<PruningTable>,
+}
+
+impl TableRegistry {
+    /// Creates an empty registry. With a cache directory, tables are loaded from and saved to the disk cache there.
+    pub fn new(cache_dir: Option<PathBuf>) -> Self {
+        Self {
+            cache_dir,
+            progress: RwLock::new(None),
+            cache_error: RwLock::new(None),
+            cancellation: RwLock::new(CancellationToken::new()),
+            move_tables: Mutex::new(HashMap::new()),
+            pruning_tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The registry shared by the whole program, which uses the disk cache in cache::get_cache_dir.
+    /// In tests it has no cache directory.
+    pub fn global() -> &'static Self {
+        &GLOBAL_REGISTRY
+    }
+
+    /// Sets the callback told about the progress of every table generated from now on
+    pub fn set_progress_callback(&self, callback: impl Fn(&str, &Progress) + Send + Sync + 'static) {
+        *self.progress.write().unwrap() = Some(Arc::new(callback));
+    }
+
+    /// Sets the callback told when a generated table can not be saved to the disk cache
+    pub fn set_cache_error_callback(&self, callback: impl Fn(&Path, &io::Error) + Send + Sync + 'static) {
+        *self.cache_error.write().unwrap() = Some(Arc::new(callback));
+    }
+
+    /// Sets the token that cancels generating tables from now on
+    pub fn set_cancellation_token(&self, cancellation: CancellationToken) {
+        *self.cancellation.write().unwrap() = cancellation;
+    }
+
+    // Tells the cache error callback about a table that could not be saved, then hands the table on
+    fn report_cache_error<T>(&self, dir: &Path, header: &TableHeader, (table, write_error): (T, Option<io::Error>)) -> T {
+        if let (Some(error), Some(callback)) = (write_error, self.cache_error.read().unwrap().as_ref()) {
+            callback(&dir.join(header.get_file_name()), &error);
+        }
+        table
+    }
+
+    fn get_options(&self, identity: &str) -> GenerationOptions {
+        let progress = self.progress.read().unwrap().clone().map(|callback| {
+            let identity = identity.to_string();
+            Arc::new(move |progress: &Progress| callback(&identity, progress)) as Arc<dyn Fn(&Progress) + Send + Sync>
+        });
+        GenerationOptions::new(progress, self.cancellation.read().unwrap().clone())
+    }
+
+    fn get_or_build<T>(slots: &Slots<T>, key: TableKey, build: impl FnOnce() -> error::Result<T>) -> error::Result<Arc<T>> {
+        let slot = slots.lock().unwrap().entry(key).or_default().clone();
+        let mut slot = slot.lock().unwrap();
+        if let Some(table) = slot.as_ref() {
+            return Ok(table.clone());
+        }
+        let table = Arc::new(build()?);
+        *slot = Some(table.clone());
+        Ok(table)
+    }
+
+    /// Returns the move tables for a coordinate, building them with generate if they have not been built yet.
+    /// The identity names the coordinate, so it must be different for every way of generating tables.
+    pub fn get_move_tables(&self, identity: &str, move_set: &[Turn], size: usize,
+            generate: impl FnOnce(&GenerationOptions) -> error::Result<MoveTables>) -> error::Result<Arc<MoveTables>> {
+        let options = self.get_options(identity);
+        let generate = || generate(&options);
+        let key = TableKey::new(TableKind::Move, identity, move_set, None);
+        Self::get_or_build(&self.move_tables, key, || {
+            let header = TableHeader::new(TableKind::Move, identity, move_set, size);
+            if let Some(bytes) = embedded::get_embedded_table(&header)? {
+                return MoveTables::from_bytes(bytes, size);
+            }
+            match &self.cache_dir {
+                Some(dir) => Ok(self.report_cache_error(dir, &header, cache::load_or_generate_move_tables(dir, identity, move_set, size, generate)?)),
+                None => generate(),
+            }
+        })
+    }
+
+    /// Shared version of MoveTables::new_basic_table
+    pub fn get_basic_move_tables<C: BasicCoordinate>(&self, coord_type: C, move_set: &[Turn]) -> error::Result<Arc<MoveTables>> {
+        self.get_move_tables(&format!("{:?}", coord_type), move_set, coord_type.get_size(),
+            |options| MoveTables::new_basic_table_with_options(coord_type, move_set, options))
+    }
+
+    /// Shared version of PruningTable::new_with_encoding, for the turns of the given move tables
+    pub fn get_pruning_table<C: Coordinate>(&self, coord_type: C, move_tables: &MoveTables, encoding: PruningEncoding) -> error::Result<Arc<PruningTable>> {
+        self.get_or_build_pruning_table(coord_type, &format!("{:?}", coord_type), move_tables, encoding, &GoalSet::solved(coord_type))
+    }
+
+    /// Shared version of PruningTable::new_with_goals. The goal name is added to the identity of the table
+    /// in the disk cache, so it should be different for every goal set of the coordinate.
+    pub fn get_pruning_table_with_goals<C: Coordinate>(&self, coord_type: C, move_tables: &MoveTables, encoding: PruningEncoding,
+            goal_name: &str, goals: &GoalSet) -> error::Result<Arc<PruningTable>> {
+        self.get_or_build_pruning_table(coord_type, &format!("{:?}-{}", coord_type, goal_name), move_tables, encoding, goals)
+    }
+
+    fn get_or_build_pruning_table<C: Coordinate>(&self, coord_type: C, identity: &str, move_tables: &MoveTables, encoding: PruningEncoding,
+            goals: &GoalSet) -> error::Result<Arc<PruningTable>> {
+        let size = coord_type.get_size();
+        let options = self.get_options(identity);
+        let generate = || PruningTable::new_with_options(coord_type, move_tables, encoding, goals, &options);
+        let move_set = move_tables.get_turns();
+        let key = TableKey::new(TableKind::Pruning(encoding), identity, move_set, Some(goals));
+        Self::get_or_build(&self.pruning_tables, key, || {
+            let header = TableHeader::new(TableKind::Pruning(encoding), identity, move_set, size);
+            if let Some(bytes) = embedded::get_embedded_table(&header)? {
+                let table = PruningTable::from_static(bytes, size, move_set, encoding)?;
+                if table.get_goal_coords() == goals.get_coords() {
+                    return Ok(table);
+                }
+            }
+            match &self.cache_dir {
+                Some(dir) => Ok(self.report_cache_error(dir, &header,
+                    cache::load_or_generate_pruning_table(dir, identity, move_set, size, encoding, goals, generate)?)),
+                None => generate(),
+            }
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::coords::basic_coords::co_ud::COUDCoord;
+    use crate::coords::basic_coords::eo_fb::EOFBCoord;
+    use crate::coords::basic_coords::u_d_corner_perms::UCornerPermCoord;
+
+    #[test]
+    fn test_shared_tables() {
+        let registry = TableRegistry::new(None);
+        let eo = EOFBCoord::new();
+        let co = COUDCoord::new();
+
+        // Every thread gets the same tables, which are only built once
+        let tables: Vec<Arc<MoveTables>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| registry.get_basic_move_tables(eo, &MoveSet::full()).unwrap()))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert!(tables.iter().all(|table| Arc::ptr_eq(table, &tables[0])));
+
+        let dr_tables = registry.get_basic_move_tables(eo, &MoveSet::dr_ud()).unwrap();
+        assert!(!Arc::ptr_eq(&dr_tables, &tables[0]));
+        let co_tables = registry.get_basic_move_tables(co, &MoveSet::full()).unwrap();
+        assert_eq!(co_tables.get_size(), co.get_size());
+
+        let pruning_table = registry.get_pruning_table(eo, &tables[0], PruningEncoding::Byte).unwrap();
+        assert!(Arc::ptr_eq(&pruning_table, &registry.get_pruning_table(eo, &tables[0], PruningEncoding::Byte).unwrap()));
+        assert!(!Arc::ptr_eq(&pruning_table, &registry.get_pruning_table(eo, &tables[0], PruningEncoding::Nibble).unwrap()));
+
+        // Tables towards other goals are kept apart from the solved one
+        let goals = GoalSet::from_coords(&[0, 1]);
+        let goal_table = registry.get_pruning_table_with_goals(eo, &tables[0], PruningEncoding::Byte, "test", &goals).unwrap();
+        assert_eq!(goal_table.get_goal_coords(), goals.get_coords());
+        assert!(!Arc::ptr_eq(&goal_table, &pruning_table));
+        assert!(Arc::ptr_eq(&goal_table, &registry.get_pruning_table_with_goals(eo, &tables[0], PruningEncoding::Byte, "test", &goals).unwrap()));
+
+        // A table that can not be built is not stored
+        assert!(registry.get_pruning_table(eo, &dr_tables, PruningEncoding::Byte).is_err());
+        assert!(registry.get_pruning_table(eo, &dr_tables, PruningEncoding::Byte).is_err());
+    }
+
+    #[test]
+    fn test_cache_errors() {
+        // A file where the cache directory should be means no table can be saved there
+        let file = std::env::temp_dir().join(format!("cube-solver-registry-file-{}", std::process::id()));
+        std::fs::write(&file, []).unwrap();
+        let registry = TableRegistry::new(Some(file.clone()));
+        let failed_paths = Arc::new(Mutex::new(Vec::new()));
+        let reported = failed_paths.clone();
+        registry.set_cache_error_callback(move |path, _| reported.lock().unwrap().push(path.to_path_buf()));
+
+        // Tables are still handed out when they can not be cached. The coordinate is not one whose tables
+        // are embedded, so they are always generated.
+        let u_corners = UCornerPermCoord::new();
+        let move_tables = registry.get_basic_move_tables(u_corners, &MoveSet::full()).unwrap();
+        registry.get_pruning_table(u_corners, &move_tables, PruningEncoding::Byte).unwrap();
+        let failed_paths = failed_paths.lock().unwrap();
+        assert_eq!(failed_paths.len(), 2);
+        assert!(failed_paths.iter().all(|path| path.starts_with(&file)));
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let registry = TableRegistry::new(None);
+        let cancellation = CancellationToken::new();
+        registry.set_cancellation_token(cancellation.clone());
+        cancellation.cancel();
+        let u_corners = UCornerPermCoord::new();
+        assert!(matches!(registry.get_basic_move_tables(u_corners, &MoveSet::full()), Err(error::Error::Cancelled)));
+
+        // A cancelled table is not stored, so it is built again with a new token
+        registry.set_cancellation_token(CancellationToken::new());
+        assert!(registry.get_basic_move_tables(u_corners, &MoveSet::full()).is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+use crate::turndef::Turn;
+
+/// SuccessorTable lists which turns may follow each turn in a canonical sequence, so that searches
+/// never try sequences like R R, or both U D and D U. Turns are referred to by their index in the move set.
+pub struct SuccessorTable {
+    turns: Vec<Turn>,
+    initial_turns: Vec<usize>,
+    successors: Vec<Vec<usize>>,
+}
+
+impl SuccessorTable {
+    pub fn new(turns: &[Turn]) -> Self {
+        let mut successors = Vec::with_capacity(turns.len());
+        for previous in turns {
+            let mut allowed = Vec::new();
+            for (index, turn) in turns.iter().enumerate() {
+                if turn.can_follow(previous) {
+                    allowed.push(index);
+                }
+            }
+            successors.push(allowed);
+        }
+
+        Self {
+            turns: turns.to_vec(),
+            initial_turns: (0..turns.len()).collect(),
+            successors,
+        }
+    }
+
+    pub fn get_turn(&self, index: usize) -> &Turn {
+        &self.turns[index]
+    }
+
+    /// Returns the indices of the turns that may follow the turn with the given index,
+    /// or every turn if there is no previous turn
+    pub fn get_successors(&self, previous: Option<usize>) -> &[usize] {
+        match previous {
+            Some(index) => &self.successors[index],
+            None => &self.initial_turns,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_sequence_count() {
+        // There are 18 * 18 = 324 sequences of two outer layer turns. Excluding 54 on the same layer
+        // and 27 with turns on opposite layers in the wrong order leaves 243.
+        let table = SuccessorTable::new(&Turn::get_outer_layer_turns());
+        let mut count = 0;
+        for first in table.get_successors(None) {
+            count += table.get_successors(Some(*first)).len();
+        }
+        assert_eq!(count, 243);
+    }
+}
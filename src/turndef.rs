@@ -88,6 +88,10 @@ const INNER_LAYER_SHIFT: u32 = 26;
 const BACK_LAYER_SHIFT: u32 = 28;
 const FB_MIRROR_SHIFT: u32 = 30;
 
+const LR_AXIS_MASK: u32 = 0xff << RIGHT_LAYER_SHIFT;
+const UD_AXIS_MASK: u32 = 0xff << TOP_LAYER_SHIFT;
+const FB_AXIS_MASK: u32 = 0xff << FRONT_LAYER_SHIFT;
+
 struct TurnNameMap {
     name: &'static str,
     turn: Turn,
@@ -170,6 +174,12 @@ impl Into<u32> for &Turn {
     }
 }
 
+impl From<u32> for Turn {
+    fn from(bits: u32) -> Self {
+        Turn(bits)
+    }
+}
+
 impl Turn {
     pub const RIGHT: Turn = Turn(BASE_TURN << RIGHT_LAYER_SHIFT);
     pub const MIDDLE: Turn = Turn(BASE_TURN << MIDDLE_LAYER_SHIFT);
@@ -185,13 +195,19 @@ impl Turn {
     pub const FB_MIRROR: Turn = Turn(BASE_TURN << FB_MIRROR_SHIFT);
 
     pub fn from_name(turn_name: &str) -> Self {
+        match Self::try_from_name(turn_name) {
+            Some(turn) => turn,
+            None => panic!("Invalid turn: {:?}", turn_name),
+        }
+    }
+
+    pub fn try_from_name(turn_name: &str) -> Option<Self> {
         for turn in TurnNameMap::get_all_turn_name_maps() {
             if turn.name == turn_name {
-                return turn.turn;
+                return Some(turn.turn);
             }
         }
-        panic!("Invalid turn: {:?}", turn_name);
-
+        None
     }
 
     pub fn to_name(&self) -> String {
@@ -271,13 +287,17 @@ impl Turn {
     }
 
     pub fn invert(&self) -> Turn {
-        if self.0 % DOUBLE_TURN == 0 {
-            Turn(self.0)
-        } else if self.0 % INVERSE_TURN == 0 {
-            Turn(self.0 / INVERSE_TURN)
-        } else  {
-            Turn(self.0 * INVERSE_TURN)
+        // Each layer is inverted separately. Mirrors are their own inverse.
+        let mut inverted = 0;
+        for shift in (0..32).step_by(2) {
+            let count = (self.0 >> shift) & 0b11;
+            if [LR_MIRROR_SHIFT, UD_MIRROR_SHIFT, FB_MIRROR_SHIFT].contains(&shift) {
+                inverted |= count << shift;
+            } else {
+                inverted |= ((4 - count) % 4) << shift;
+            }
         }
+        Turn(inverted)
     }
 
     pub fn get_outer_layer_turns() -> Vec<Self> {
@@ -289,11 +309,74 @@ impl Turn {
         }
         turns
     }
+
+    /// Returns the distinct turns made by repeating this turn, such as R, R2 and R' for R, or just R2 for R2
+    pub fn get_powers(&self) -> Vec<Turn> {
+        let mut powers = Vec::new();
+        for power in 1..4 {
+            let mut turn = 0;
+            for shift in (0..32).step_by(2) {
+                let count = (self.0 >> shift) & 0b11;
+                turn |= ((count * power) % 4) << shift;
+            }
+            if turn != 0 && !powers.contains(&Turn(turn)) {
+                powers.push(Turn(turn));
+            }
+        }
+        powers
+    }
+
+    /*
+    Each layer occupies a 2-bit field. Two turns act on the same layers if the same fields are non-zero,
+    regardless of how many times each layer is turned.
+    */
+    fn get_layer_mask(&self) -> u32 {
+        let mut mask = 0;
+        for shift in (0..32).step_by(2) {
+            if (self.0 >> shift) & 0b11 != 0 {
+                mask |= 0b11 << shift;
+            }
+        }
+        mask
+    }
+
+    pub fn is_same_layer(&self, other: &Turn) -> bool {
+        self.get_layer_mask() == other.get_layer_mask()
+    }
+
+    /// Returns the bits of the axis this turn is about, or 0 if it turns layers on more than one axis
+    pub fn get_axis_mask(&self) -> u32 {
+        for mask in [LR_AXIS_MASK, UD_AXIS_MASK, FB_AXIS_MASK] {
+            if self.0 & !mask == 0 {
+                return mask;
+            }
+        }
+        0
+    }
+
+    pub fn is_same_axis(&self, other: &Turn) -> bool {
+        let axis = self.get_axis_mask();
+        axis != 0 && axis == other.get_axis_mask()
+    }
+
+    /// Whether this turn may follow the previous turn in a canonical sequence. Turning the same layer
+    /// twice in a row is never useful, and turns on the same axis commute, so they must be ordered by layer.
+    pub fn can_follow(&self, previous: &Turn) -> bool {
+        if self.is_same_layer(previous) {
+            return false;
+        }
+        if self.is_same_axis(previous) {
+            return self.get_layer_mask() > previous.get_layer_mask();
+        }
+        true
+    }
 }
 
 pub trait Algorithm {
     fn to_base_turns(&self) -> Vec<Turn>;
     fn to_algorithm_string(&self) -> String;
+    fn invert(&self) -> Vec<Turn>;
+    fn cancel_turns(&self) -> Vec<Turn>;
 }
 
 impl Algorithm for Vec<Turn> {
@@ -304,6 +387,14 @@ impl Algorithm for Vec<Turn> {
     fn to_algorithm_string(&self) -> String {
         to_algorithm_string(self.as_slice())
     }
+
+    fn invert(&self) -> Vec<Turn> {
+        invert(self.as_slice())
+    }
+
+    fn cancel_turns(&self) -> Vec<Turn> {
+        cancel_turns(self.as_slice())
+    }
 }
 
 impl Algorithm for &[Turn] {
@@ -314,6 +405,14 @@ impl Algorithm for &[Turn] {
     fn to_algorithm_string(&self) -> String {
         to_algorithm_string(self)
     }
+
+    fn invert(&self) -> Vec<Turn> {
+        invert(self)
+    }
+
+    fn cancel_turns(&self) -> Vec<Turn> {
+        cancel_turns(self)
+    }
 }
 
 fn to_base_turns(turns: &[Turn]) -> Vec<Turn> {
@@ -324,6 +423,55 @@ fn to_base_turns(turns: &[Turn]) -> Vec<Turn> {
     base_turns
 }
 
+fn invert(turns: &[Turn]) -> Vec<Turn> {
+    turns.iter().rev().map(|turn| turn.invert()).collect()
+}
+
+/*
+Turns on the same axis commute, so a run of them can be merged by counting how many quarter turns
+each layer makes. Layers that turn a multiple of 4 times disappear, which can bring two runs on the
+same axis together, so we keep merging until nothing changes.
+*/
+fn cancel_turns(turns: &[Turn]) -> Vec<Turn> {
+    let mut cancelled = turns.to_vec();
+    loop {
+        let merged = merge_same_axis_runs(&cancelled);
+        if merged.len() == cancelled.len() {
+            return merged;
+        }
+        cancelled = merged;
+    }
+}
+
+fn merge_same_axis_runs(turns: &[Turn]) -> Vec<Turn> {
+    let mut merged = Vec::new();
+    let mut start = 0;
+    while start < turns.len() {
+        let mut end = start + 1;
+        while end < turns.len() && turns[end].is_same_axis(&turns[start]) {
+            end += 1;
+        }
+        if end - start == 1 {
+            merged.push(turns[start]);
+        } else {
+            let mut layer_counts: Vec<(Turn, u32)> = Vec::new();
+            for base_turn in turns[start..end].to_vec().to_base_turns() {
+                match layer_counts.iter_mut().find(|(layer, _)| *layer == base_turn) {
+                    Some((_, count)) => *count += 1,
+                    None => layer_counts.push((base_turn, 1)),
+                }
+            }
+            for (layer, count) in layer_counts {
+                if count % 4 != 0 {
+                    merged.push(layer * (count % 4));
+                }
+            }
+        }
+        start = end;
+    }
+    merged
+}
+
 fn to_algorithm_string(turns: &[Turn]) -> String {
     let mut alg = String::new();
     for turn in turns {
@@ -516,4 +664,48 @@ mod tests {
 
 
     }
+
+    #[test]
+    fn test_get_powers() {
+        assert_eq!(Turn::from_name("R").get_powers(), Turn::get_vec_from_alg_string("R R2 R'"));
+        assert_eq!(Turn::from_name("L").get_powers(), Turn::get_vec_from_alg_string("L L2 L'"));
+        assert_eq!(Turn::from_name("F2").get_powers(), Turn::get_vec_from_alg_string("F2"));
+    }
+
+    #[test]
+    fn test_can_follow() {
+        let can_follow = |turn: &str, previous: &str| Turn::from_name(turn).can_follow(&Turn::from_name(previous));
+        assert!(can_follow("U", "R"));
+        assert!(!can_follow("U", "U2"));
+        assert!(can_follow("D", "U"));
+        assert!(!can_follow("U", "D'"));
+        assert!(can_follow("L2", "R"));
+        assert!(!can_follow("R", "L2"));
+    }
+
+    #[test]
+    fn test_invert() {
+        let alg = Turn::get_vec_from_alg_string("R U2 F' M");
+        assert_eq!(alg.invert().to_algorithm_string(), "M' F U2 R'");
+    }
+
+    #[test]
+    fn test_cancel_turns() {
+        let cancel = |alg: &str| Turn::get_vec_from_alg_string(alg).cancel_turns().to_algorithm_string();
+        assert_eq!(cancel("R U F"), "R U F");
+        assert_eq!(cancel("R U U F"), "R U2 F");
+        assert_eq!(cancel("R U U' F"), "R F");
+        assert_eq!(cancel("R U D U' F"), "R D F");
+        assert_eq!(cancel("R U2 D U2 F"), "R D F");
+        assert_eq!(cancel("R U U' R"), "R2");
+        assert_eq!(cancel("R L R'"), "L");
+    }
+
+    #[test]
+    fn test_turn_invert() {
+        for (name, inverse) in [("R", "R'"), ("U'", "U"), ("F2", "F2"), ("M", "M'"), ("r", "r'"), ("b2", "b2")] {
+            assert_eq!(Turn::from_name(name).invert(), Turn::from_name(inverse));
+        }
+        assert_eq!(Turn::from_name("lr_mirror").invert(), Turn::from_name("lr_mirror"));
+    }
 }
\ No newline at end of file
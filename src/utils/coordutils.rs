@@ -186,6 +186,9 @@ pub fn coord_to_piece_distribution(mut coord: usize, num_positions: usize, num_p
     let mut num_left = num_pieces_of_interest;
 
     for j in 0..num_positions {
+        if num_left == 0 {
+            break;
+        }
         let n = num_positions - j - 1;
         let n_choose_k = binomial(n, num_left-1);
         if coord >= n_choose_k {
@@ -195,9 +198,6 @@ pub fn coord_to_piece_distribution(mut coord: usize, num_positions: usize, num_p
             state[j] = true;
             num_left -= 1;
         }
-        if num_left == 0 {
-            break;
-        }
     }
     state
 }
@@ -227,6 +227,54 @@ pub fn get_perm_for_distribution_coord<T: PieceState + Copy>(coord:usize, in_gro
     pieces
 }
 
+/*
+Represent where a subset of the pieces are and how they are permuted amongst themselves. The coordinate is
+the distribution coordinate of the subset, times the number of permutations of the subset, plus the
+permutation coordinate of the subset. The subset must be given in ascending order.
+For 4 of 12 edges, this gives 495 * 24 = 11 880 possible states.
+*/
+pub const fn get_piece_subset_size(num_positions: usize, num_pieces: usize) -> usize {
+    let mut size = 1;
+    let mut i = 0;
+    while i < num_pieces {
+        size *= num_positions - i;
+        i += 1;
+    }
+    size
+}
+
+pub fn piece_subset_to_coord<T: PartialOrd + Copy>(pieces: &[T], subset: &[T]) -> usize {
+    let mut in_group = Vec::with_capacity(pieces.len());
+    let mut in_group_pieces = Vec::with_capacity(subset.len());
+    for piece in pieces {
+        if subset.contains(piece) {
+            in_group_pieces.push(*piece);
+            in_group.push(true);
+        } else {
+            in_group.push(false);
+        }
+    }
+    let dist_coord = piece_distibution_to_coord(&in_group);
+    let perm_coord = permutation_to_coord(&in_group_pieces);
+    dist_coord * factorial(subset.len()) + perm_coord
+}
+
+/*
+Given a piece subset coordinate, the subset and all of the pieces, finds an arrangement of the pieces
+with that coordinate. The pieces outside of the subset keep the order they are given in.
+*/
+pub fn coord_to_piece_subset<T: PieceState + PartialOrd + Copy>(coord: usize, subset: &[T], all_pieces: &[T]) -> Vec<T> {
+    let num_perms = factorial(subset.len());
+    let in_group_pieces: Vec<T> = coord_to_permutation(coord % num_perms, subset.len()).into_iter()
+        .map(|index| subset[index])
+        .collect();
+    let out_of_group_pieces: Vec<T> = all_pieces.iter()
+        .filter(|piece| !subset.contains(piece))
+        .copied()
+        .collect();
+    get_perm_for_distribution_coord(coord / num_perms, &in_group_pieces, &out_of_group_pieces)
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -442,6 +490,7 @@ mod tests {
         assert_eq!(coord_to_piece_distribution(494, 12, 4),
             &[false, false, false, false, false, false, false, false, true, true, true, true]
         );
+        assert_eq!(coord_to_piece_distribution(0, 3, 0), &[false, false, false]);
     }
 
     #[test]
@@ -456,4 +505,27 @@ mod tests {
         assert_eq!(is_even_parity(&[11,10,9,8,7,6,5,4,3,2,1,0]), true);
     }
 
+    #[test]
+    fn test_piece_subset_to_coord() {
+        use crate::rawcube::Corner;
+
+        let all_corners = [Corner::UBL, Corner::UFL, Corner::UFR, Corner::UBR, Corner::DBL, Corner::DFL, Corner::DFR, Corner::DBR];
+        let subset = [Corner::UFR, Corner::DFR];
+        let size = get_piece_subset_size(8, 2);
+        assert_eq!(size, 56);
+        assert_eq!(get_piece_subset_size(12, 4), 11_880);
+        assert_eq!(get_piece_subset_size(8, 0), 1);
+
+        // Every coordinate puts the subset in a different arrangement
+        let mut arrangements = Vec::new();
+        for coord in 0..size {
+            let pieces = coord_to_piece_subset(coord, &subset, &all_corners);
+            assert_eq!(piece_subset_to_coord(&pieces, &subset), coord);
+            let arrangement: Vec<usize> = subset.iter().map(|piece| pieces.iter().position(|other| other == piece).unwrap()).collect();
+            assert!(!arrangements.contains(&arrangement));
+            arrangements.push(arrangement);
+        }
+        assert_eq!(coord_to_piece_subset(0, &subset, &all_corners)[..2], subset);
+    }
+
 }
\ No newline at end of file